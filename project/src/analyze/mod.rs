@@ -1,54 +1,257 @@
-use crate::graph::Graph;
-use crate::graph::Edge;
+pub mod status;
+
+use crate::graph::{ClusteringVariant, Graph, Network};
+use crate::graph::balance::{structural_balance, StructuralBalance};
+use crate::graph::algorithm::{betweenness_centrality, BetweennessOptions};
+use crate::graph::algorithm::{closeness_centrality, harmonic_centrality, Direction};
+use crate::graph::algorithm::{core_numbers, hits, DegreeKind, HitsOptions};
+use crate::graph::algorithm::{triangle_stats, TriangleStats};
+use crate::graph::algorithm::{diameter, weak_components, Diameter, WeakComponents};
+use crate::graph::community::Partition;
+use crate::graph::reputation::{eigentrust, EigenTrustOptions};
+use crate::graph::reputation::{fairness_goodness, pagerank, FairnessGoodnessOptions, PageRankOptions};
+use crate::graph::reputation::FairnessGoodness;
+use status::{status_report, StatusReport};
+use std::cell::{OnceCell, RefCell};
 use std::collections::HashMap;
+#[cfg(test)]
+use crate::graph::Edge;
+#[cfg(test)]
+use crate::graph::community::{louvain, LouvainOptions};
+#[cfg(test)]
+use crate::graph::csr::CsrGraph;
+
+// Which per node score counts as "centrality" in the analysis functions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CentralityMeasure {
+    InDegree,
+    OutDegree,
+    Betweenness(BetweennessOptions),
+    Closeness(Direction),
+    Harmonic(Direction),
+    PageRank,
+    Authority(HitsOptions),     // HITS authority, how well rated by good raters
+}
 
+// Size, average trust score and average clustering coefficient of a community.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommunitySummary {
+    pub size: usize,
+    pub average_trust: f64,
+    pub average_clustering: f64,
+}
+
+// Degrees, trust scores and components are computed up front and stay pub
+// fields. Every other analysis is a method (pagerank(), sub_graphs(), ...)
+// that computes it the first time it is asked for, so a command only pays
+// for what it prints. G is the graph type it works on, Graph or csr::CsrGraph.
 #[derive(Debug, Clone)]
-pub struct GraphInfo {
-    pub graph: Graph,
+pub struct GraphInfo<G = Graph> {
+    pub graph: G,
     pub nodes_indegree: HashMap<usize, f64>,
     pub nodes_outdegree: HashMap<usize, f64>,
-    pub clustering_coefficients: HashMap<usize, f64>,
-    pub sub_graphs: Vec<Graph>,
     pub trust_scores: HashMap<usize, f64>,
+    pub components: WeakComponents,
+    pub clustering_variant: ClusteringVariant,      // Which clustering_coefficients() computes
+    closeness_in: OnceCell<HashMap<usize, f64>>,
+    closeness_out: OnceCell<HashMap<usize, f64>>,
+    harmonic_in: OnceCell<HashMap<usize, f64>>,
+    harmonic_out: OnceCell<HashMap<usize, f64>>,
+    clustering_coefficients: OnceCell<HashMap<usize, f64>>,
+    sub_graphs: OnceCell<Vec<Graph>>,
+    sub_graph_diameters: OnceCell<Vec<Diameter>>,
+    strong_sub_graphs: OnceCell<Vec<Graph>>,
+    fairness_goodness: OnceCell<FairnessGoodness>,
+    pagerank: OnceCell<HashMap<usize, f64>>,
+    eigentrust: OnceCell<HashMap<usize, f64>>,
+    balance: OnceCell<StructuralBalance>,
+    core_numbers: OnceCell<HashMap<usize, usize>>,
+    triangles: OnceCell<TriangleStats>,
+    computed_centralities: RefCell<Vec<(CentralityMeasure, HashMap<usize, f64>)>>,  // Betweenness / authority scores computed so far
 }
 
-impl GraphInfo {
+impl<G: Network + Clone> GraphInfo<G> {
     // Constructor to get all the graph info of the data using
-    // methods from Graph.
-    pub fn get_info(graph: &Graph) -> GraphInfo {
+    // the Network methods of the graph.
+    pub fn get_info(graph: &G) -> GraphInfo<G> {
+        GraphInfo::get_info_with(graph, ClusteringVariant::Unweighted)
+    }
+
+    // Same as get_info with a chosen clustering coefficient variant.
+    pub fn get_info_with(graph: &G, clustering_variant: ClusteringVariant) -> GraphInfo<G> {
         // Indegrees and outdegrees
         let (nodes_indegree, nodes_outdegree) = graph.get_degrees();
 
-        // Clustering coefficients
-        let mut clustering_coefficients = HashMap::new();
-        for &node in graph.content.keys() {
-            clustering_coefficients.insert(node, graph.clustering_coefficient(node));
-        }
-
-        // Clustering coefficients
+        // Trust scores
         let mut trust_scores = HashMap::new();
-        for &node in graph.content.keys() {
+        for node in graph.nodes() {
             trust_scores.insert(node, graph.get_trust_score(node));
         }
 
-        // Subgraphs
-        let sub_graphs = graph.find_subgraphs();
+        // Weakly connected components, sub_graphs()[i] is component i
+        let components = weak_components(graph);
 
         GraphInfo {
             graph: graph.clone(),
-            nodes_indegree: nodes_indegree,
-            nodes_outdegree: nodes_outdegree,
-            clustering_coefficients: clustering_coefficients,
-            sub_graphs: sub_graphs,
-            trust_scores: trust_scores,
+            nodes_indegree,
+            nodes_outdegree,
+            trust_scores,
+            components,
+            clustering_variant,
+            closeness_in: OnceCell::new(),
+            closeness_out: OnceCell::new(),
+            harmonic_in: OnceCell::new(),
+            harmonic_out: OnceCell::new(),
+            clustering_coefficients: OnceCell::new(),
+            sub_graphs: OnceCell::new(),
+            sub_graph_diameters: OnceCell::new(),
+            strong_sub_graphs: OnceCell::new(),
+            fairness_goodness: OnceCell::new(),
+            pagerank: OnceCell::new(),
+            eigentrust: OnceCell::new(),
+            balance: OnceCell::new(),
+            core_numbers: OnceCell::new(),
+            triangles: OnceCell::new(),
+            computed_centralities: RefCell::new(Vec::new()),
         }
     }
 
+    // Closeness centrality of every node in the given direction.
+    pub fn closeness(&self, direction: Direction) -> &HashMap<usize, f64> {
+        let cell = match direction {
+            Direction::In => &self.closeness_in,
+            Direction::Out => &self.closeness_out,
+        };
+        cell.get_or_init(|| closeness_centrality(&self.graph, direction))
+    }
+
+    // Harmonic centrality of every node in the given direction.
+    pub fn harmonic(&self, direction: Direction) -> &HashMap<usize, f64> {
+        let cell = match direction {
+            Direction::In => &self.harmonic_in,
+            Direction::Out => &self.harmonic_out,
+        };
+        cell.get_or_init(|| harmonic_centrality(&self.graph, direction))
+    }
+
+    // Clustering coefficients of the clustering_variant, the unweighted
+    // ones come with the triangles.
+    pub fn clustering_coefficients(&self) -> &HashMap<usize, f64> {
+        self.clustering_coefficients.get_or_init(|| match self.clustering_variant {
+            ClusteringVariant::Unweighted => self.triangles().rating_clustering.clone(),
+            variant => self.graph.clustering_coefficients(variant),
+        })
+    }
+
+    // Subgraph of every weakly connected component, in components order.
+    pub fn sub_graphs(&self) -> &[Graph] {
+        self.sub_graphs.get_or_init(|| self.graph.component_subgraphs(&self.components))
+    }
+
+    // Diameter of every subgraph in sub_graphs.
+    pub fn sub_graph_diameters(&self) -> &[Diameter] {
+        self.sub_graph_diameters.get_or_init(|| self.sub_graphs().iter().map(diameter).collect())
+    }
+
+    // Strongly connected subgraphs, following edge direction.
+    pub fn strong_sub_graphs(&self) -> &[Graph] {
+        self.strong_sub_graphs.get_or_init(|| self.graph.find_strong_subgraphs())
+    }
+
+    // Goodness and fairness, trust weighted by how reliable the raters are.
+    pub fn fairness_goodness(&self) -> &FairnessGoodness {
+        self.fairness_goodness.get_or_init(|| fairness_goodness(&self.graph, &FairnessGoodnessOptions::default()))
+    }
+
+    // PageRank over positive ratings.
+    pub fn pagerank(&self) -> &HashMap<usize, f64> {
+        self.pagerank.get_or_init(|| pagerank(&self.graph, &PageRankOptions::default()))
+    }
+
+    // EigenTrust without pre-trusted peers.
+    pub fn eigentrust(&self) -> &HashMap<usize, f64> {
+        self.eigentrust.get_or_init(|| eigentrust(&self.graph, &[], &EigenTrustOptions::default()))
+    }
+
+    // Signed triangles of the undirected graph.
+    pub fn balance(&self) -> &StructuralBalance {
+        self.balance.get_or_init(|| structural_balance(&self.graph))
+    }
+
+    // k-core number of every node by total degree.
+    pub fn core_numbers(&self) -> &HashMap<usize, usize> {
+        self.core_numbers.get_or_init(|| core_numbers(&self.graph, DegreeKind::Total))
+    }
+
+    // Triangles, transitivity and undirected / directed clustering.
+    pub fn triangles(&self) -> &TriangleStats {
+        self.triangles.get_or_init(|| triangle_stats(&self.graph))
+    }
+
+    // Index in sub_graphs() of the subgraph containing node.
+    pub fn component_of(&self, node: usize) -> Option<usize> {
+        self.components.component_of(node)
+    }
+
+    // Number of nodes in each strongly connected subgraph, largest first.
+    pub fn strong_component_sizes(&self) -> Vec<usize> {
+        let mut sizes: Vec<usize> = self.strong_sub_graphs().iter().map(|sub_graph| sub_graph.node_count()).collect();
+        sizes.sort_by(|a, b| b.cmp(a));
+        sizes
+    }
+
+    // Core number of the innermost (densest) core and the number of nodes in it.
+    pub fn innermost_core(&self) -> (usize, usize) {
+        let max_core = self.core_numbers().values().copied().max().unwrap_or(0);
+        let size = self.core_numbers().values().filter(|&&core| core == max_core).count();
+        (max_core, size)
+    }
+
+    // Centrality of every node for the given measure. Degrees are already
+    // stored, the other measures are computed on demand.
+    pub fn centrality(&self, measure: CentralityMeasure) -> HashMap<usize, f64> {
+        match measure {
+            CentralityMeasure::InDegree => self.nodes_indegree.clone(),
+            CentralityMeasure::OutDegree => self.nodes_outdegree.clone(),
+            CentralityMeasure::Closeness(Direction::In) => self.closeness(Direction::In).clone(),
+            CentralityMeasure::Closeness(Direction::Out) => self.closeness(Direction::Out).clone(),
+            CentralityMeasure::Harmonic(Direction::In) => self.harmonic(Direction::In).clone(),
+            CentralityMeasure::Harmonic(Direction::Out) => self.harmonic(Direction::Out).clone(),
+            CentralityMeasure::PageRank => self.pagerank().clone(),
+            CentralityMeasure::Betweenness(_) | CentralityMeasure::Authority(_) => self.computed_centrality(measure),
+        }
+    }
+
+    // helper that computes a measure GraphInfo doesn't store, only once, so the
+    // sections of the all command that use the same measure share it.
+    fn computed_centrality(&self, measure: CentralityMeasure) -> HashMap<usize, f64> {
+        if let Some((_, scores)) = self.computed_centralities.borrow().iter().find(|(computed, _)| *computed == measure) {
+            return scores.clone();
+        }
+        let scores = match measure {
+            CentralityMeasure::Betweenness(options) => betweenness_centrality(&self.graph, &options),
+            CentralityMeasure::Authority(options) => hits(&self.graph, &options).authorities,
+            _ => self.centrality(measure),
+        };
+        self.computed_centralities.borrow_mut().push((measure, scores.clone()));
+        scores
+    }
+
+    // Clustering and centrality breakdown of high / low trust nodes,
+    // using out-degree as the centrality.
     pub fn analyze_clustering_centrality(&self, high_score: f64, low_score: f64) -> String {
+        self.analyze_clustering_centrality_with(high_score, low_score, CentralityMeasure::OutDegree)
+    }
+
+    // Same as analyze_clustering_centrality with a chosen centrality measure.
+    // A node has high centrality if its score is at least the mean score.
+    pub fn analyze_clustering_centrality_with(&self, high_score: f64, low_score: f64, measure: CentralityMeasure) -> String {
+        let centrality = self.centrality(measure);
+
         // Filter nodes with trust score
         let mut high_trust_nodes = vec![];
         let mut low_trust_nodes = vec![];
-        for &node in self.graph.content.keys() {
+        for node in self.graph.nodes() {
             if let Some(&trust_score) = self.trust_scores.get(&node) {
                 if trust_score >= high_score {
                     high_trust_nodes.push(node);
@@ -59,11 +262,11 @@ impl GraphInfo {
             }
         }
     
-        let avg_clustering: f64 = self.clustering_coefficients.values().sum::<f64>()
-            / self.clustering_coefficients.len() as f64;
+        let avg_clustering: f64 = self.clustering_coefficients().values().sum::<f64>()
+            / self.clustering_coefficients().len() as f64;
     
-        let mean_indegree: f64 = self.nodes_outdegree.values().cloned().sum::<f64>()
-            / self.nodes_outdegree.len() as f64;
+        let mean_centrality: f64 = centrality.values().sum::<f64>()
+            / centrality.len() as f64;
     
         let mut high_clustering_count_high_score = 0;
         let mut high_centrality_count_high_score = 0;
@@ -72,14 +275,14 @@ impl GraphInfo {
     
         for &node in &high_trust_nodes {
             // Check cc to see if it is well-clusterred.
-            let clustering = self.clustering_coefficients.get(&node).unwrap_or(&0.0);
+            let clustering = self.clustering_coefficients().get(&node).unwrap_or(&0.0);
             if *clustering > avg_clustering {
                 high_clustering_count_high_score += 1;
             }
     
             // Calculate centrality
-            if let Some(&degree) = self.nodes_outdegree.get(&node) {
-                if degree as f64 >= mean_indegree {
+            if let Some(&score) = centrality.get(&node) {
+                if score >= mean_centrality {
                     high_centrality_count_high_score += 1;
                 }
             }
@@ -87,14 +290,14 @@ impl GraphInfo {
 
         for &node in &low_trust_nodes {
             // Check cc to see if it is well-clusterred.
-            let clustering = self.clustering_coefficients.get(&node).unwrap_or(&0.0);
+            let clustering = self.clustering_coefficients().get(&node).unwrap_or(&0.0);
             if *clustering > avg_clustering {
                 high_clustering_count_low_score += 1;
             }
     
             // Calculate centrality
-            if let Some(&degree) = self.nodes_outdegree.get(&node) {
-                if degree as f64 >= mean_indegree {
+            if let Some(&score) = centrality.get(&node) {
+                if score >= mean_centrality {
                     high_centrality_count_low_score += 1;
                 }
             }
@@ -122,36 +325,75 @@ impl GraphInfo {
 
     
         // Return the result
-        return format!("\nNodes with trust score >= {}: {} nodes.
+        format!("\nNodes with trust score >= {}: {} nodes.
                 \nPercentage with high clustering: {:.2}%.
                 \nPercentage with high centrality: {:.2}%.
+                \nAverage closeness (in / out): {:.4} / {:.4}.
+                \nAverage harmonic centrality (in / out): {:.2} / {:.2}.
                 \nNodes with trust score <= {}: {} nodes.
                 \nPercentage with high clustering: {:.2}%.
-                \nPercentage with high centrality: {:.2}%.",
+                \nPercentage with high centrality: {:.2}%.
+                \nAverage closeness (in / out): {:.4} / {:.4}.
+                \nAverage harmonic centrality (in / out): {:.2} / {:.2}.",
                 high_score, high_trust_count,
                 clustering_percentage_high_score, 
                 centrality_percentage_high_score,
+                Self::average_of(&high_trust_nodes, self.closeness(Direction::In)),
+                Self::average_of(&high_trust_nodes, self.closeness(Direction::Out)),
+                Self::average_of(&high_trust_nodes, self.harmonic(Direction::In)),
+                Self::average_of(&high_trust_nodes, self.harmonic(Direction::Out)),
                 low_score, low_trust_count,
                 clustering_percentage_low_score, 
                 centrality_percentage_low_score,
-                );
+                Self::average_of(&low_trust_nodes, self.closeness(Direction::In)),
+                Self::average_of(&low_trust_nodes, self.closeness(Direction::Out)),
+                Self::average_of(&low_trust_nodes, self.harmonic(Direction::In)),
+                Self::average_of(&low_trust_nodes, self.harmonic(Direction::Out)),
+                )
+    }
+
+    // Summary of every community of the partition, in community id order.
+    pub fn summarize_communities(&self, partition: &Partition) -> Vec<CommunitySummary> {
+        partition.communities().iter()
+            .map(|members| CommunitySummary {
+                size: members.len(),
+                average_trust: Self::average_of(members, &self.trust_scores),
+                average_clustering: Self::average_of(members, self.clustering_coefficients()),
+            })
+            .collect()
+    }
+
+    // Status theory analysis of the signed ratings: how each of the 16
+    // ways a third user can be linked to a rating changes its sign.
+    pub fn analyze_status(&self) -> StatusReport {
+        status_report(&self.graph)
     }
 
+    // The k nodes with the best mix of centrality and clustering,
+    // using in-degree as the centrality.
     pub fn find_k_representatives(&self, k: usize) -> String {
-        // normalize the data: centrality (# indegree), trust_scores, clustering coefficients.
-        let (min_indegree, max_indegree) = Self::find_min_max(&self.nodes_indegree);
-        let (min_clustering, max_clustering) = Self::find_min_max(&self.clustering_coefficients);
+        self.find_k_representatives_with(k, CentralityMeasure::InDegree)
+    }
+
+    // Same as find_k_representatives with a chosen centrality measure.
+    pub fn find_k_representatives_with(&self, k: usize, measure: CentralityMeasure) -> String {
+        let centrality = self.centrality(measure);
+
+        // normalize the data: centrality, trust_scores, clustering coefficients.
+        let (min_centrality, max_centrality) = Self::find_min_max(&centrality);
+        let (min_clustering, max_clustering) = Self::find_min_max(self.clustering_coefficients());
     
-        let mut node_scores: Vec<(usize, f64, f64)> = self.graph.content.keys().map(|&node| {
-            let normalized_centrality = Self::normalize(*self.nodes_indegree.get(&node).unwrap_or(&0.0) as f64 as f64, min_indegree, max_indegree);
-            let normalized_clustering =  Self::normalize(*self.clustering_coefficients.get(&node).unwrap_or(&0.0) as f64, min_clustering, max_clustering);
+        let mut node_scores: Vec<(usize, f64, f64)> = self.graph.nodes().map(|node| {
+            let normalized_centrality = Self::normalize(*centrality.get(&node).unwrap_or(&0.0), min_centrality, max_centrality);
+            let normalized_clustering =  Self::normalize(*self.clustering_coefficients().get(&node).unwrap_or(&0.0), min_clustering, max_clustering);
             let score = 0.7 * normalized_centrality + 0.3 * normalized_clustering;
             let trust_score = *self.trust_scores.get(&node).unwrap_or(&0.0);
             (node, score, trust_score)
         }).collect();
     
-        // Sort base on score then by trust score if equal to remain order.
-        node_scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(b.2.partial_cmp(&a.2).unwrap()));
+        // Sort base on score then by trust score if equal to remain order,
+        // then by node id so the result is the same on every run.
+        node_scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.2.total_cmp(&a.2)).then(a.0.cmp(&b.0)));
         
     
         // get the k representative 
@@ -164,13 +406,13 @@ impl GraphInfo {
 
         let mut clustering_sum: f64 = 0.0;
         for repr in representatives.iter() {
-            clustering_sum += self.clustering_coefficients.get(&repr).unwrap_or(&0.0);
+            clustering_sum += self.clustering_coefficients().get(repr).unwrap_or(&0.0);
         }
 
         let avg_representative_clustering: f64 = clustering_sum / representatives.len() as f64;
     
         // Ratio of num representative to total num of nodes
-        let representative_ratio = (representatives.len() as f64 / self.graph.content.len() as f64) * 100.0;
+        let representative_ratio = (representatives.len() as f64 / self.graph.node_count() as f64) * 100.0;
     
         let mut avg_trust_score = Vec::new();
     
@@ -178,21 +420,29 @@ impl GraphInfo {
             avg_trust_score.push(self.trust_scores.get(node).unwrap());
         }
     
+        // Whether the representatives sit in the dense core or on the fringe
+        let representative_cores: Vec<usize> = representatives.iter().map(|node| self.core_numbers()[node]).collect();
+        let (max_core, innermost_size) = self.innermost_core();
+
         // Prepare the result message
         let satisfaction_message = format!(
             "\nSelected Representatives: {:?}
             \nThe number of representatives is {:.2}% of total nodes.
             \nAverage trust scores of each representative: {:?}
             \nAverage trust scores of all representatives: {}
-            \nAverage clustering coefficient of representatives: {}",
+            \nAverage clustering coefficient of representatives: {}
+            \nCore numbers of representatives: {:?} (innermost core: {}-core with {} nodes)",
             representatives,
             representative_ratio,
             avg_trust_score,
             avg_trust_score.clone().into_iter().sum::<f64>() / avg_trust_score.len() as f64,
             avg_representative_clustering,
+            representative_cores,
+            max_core,
+            innermost_size,
         );
     
-        return satisfaction_message;
+        satisfaction_message
     }
 
     // Helper function to find the min and max values of a HashMap's values
@@ -218,6 +468,14 @@ impl GraphInfo {
         (min_value, max_value)
     }
 
+    // Helper function to average a per node value over some nodes (0 if there are none)
+    fn average_of(nodes: &[usize], values: &HashMap<usize, f64>) -> f64 {
+        if nodes.is_empty() {
+            return 0.0;
+        }
+        nodes.iter().map(|node| values.get(node).unwrap_or(&0.0)).sum::<f64>() / nodes.len() as f64
+    }
+
     /// Helper function to normalize a value between min and max
     fn normalize(value: f64, min: f64, max: f64) -> f64 {
         if max - min == 0.0 {
@@ -228,18 +486,67 @@ impl GraphInfo {
     }
 }  
 
+// Spearman rank correlation of two scores over the nodes they both have,
+// e.g. to compare a reputation algorithm with get_trust_score. Tied scores
+// get their average rank. None if there are less than 2 common nodes or
+// one of the scores is constant.
+pub fn spearman_correlation(a: &HashMap<usize, f64>, b: &HashMap<usize, f64>) -> Option<f64> {
+    let mut nodes: Vec<usize> = a.keys().filter(|node| b.contains_key(node)).copied().collect();
+    nodes.sort();
+    if nodes.len() < 2 {
+        return None;
+    }
+
+    let ranks_a = ranks(&nodes.iter().map(|node| a[node]).collect::<Vec<f64>>());
+    let ranks_b = ranks(&nodes.iter().map(|node| b[node]).collect::<Vec<f64>>());
+
+    // Pearson correlation of the ranks, which handles ties
+    let mean = (nodes.len() as f64 + 1.0) / 2.0;
+    let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+    for (rank_a, rank_b) in ranks_a.iter().zip(ranks_b.iter()) {
+        covariance += (rank_a - mean) * (rank_b - mean);
+        variance_a += (rank_a - mean) * (rank_a - mean);
+        variance_b += (rank_b - mean) * (rank_b - mean);
+    }
+    if variance_a == 0.0 || variance_b == 0.0 {
+        return None;
+    }
+    Some(covariance / (variance_a * variance_b).sqrt())
+}
+
+// helper that gives the 1 based rank of every value, ties get the average rank
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&i, &j| values[i].total_cmp(&values[j]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start;
+        while end + 1 < order.len() && values[order[end + 1]] == values[order[start]] {
+            end += 1;
+        }
+        let average_rank = (start + end) as f64 / 2.0 + 1.0;
+        for &index in &order[start..=end] {
+            ranks[index] = average_rank;
+        }
+        start = end + 1;
+    }
+    ranks
+}
+
 // ----------------------- TESTS ----------------------- 
 #[test]
 fn test_get_info_basic() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
-        Edge { from: 2, to: 3, weight: 2.0 },
-        Edge { from: 3, to: 1, weight: 3.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 2.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 3.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
     let info = GraphInfo::get_info(&graph);
 
-    assert_eq!(info.graph.content.len(), 3); // Should be three nodes
+    assert_eq!(info.graph.node_count(), 3); // Should be three nodes
     assert!(info.nodes_indegree.contains_key(&1)); // Should contain node 1
     assert!(info.nodes_outdegree.contains_key(&1)); // Should contain node 1
     assert!(info.nodes_indegree.get(&1).unwrap() - 1.0 < 1e-8); // Indegre should be 1
@@ -252,18 +559,39 @@ fn test_get_info_empty_graph() {
     let graph = Graph::new(&edges);
     let info = GraphInfo::get_info(&graph);
 
-    assert_eq!(info.graph.content.len(), 0); // No nodes
+    assert_eq!(info.graph.node_count(), 0); // No nodes
     assert!(info.nodes_indegree.is_empty()); // No indegrees
     assert!(info.nodes_outdegree.is_empty()); // No outdegrees
-    assert!(info.clustering_coefficients.is_empty()); // No clustering coefficients
-    assert!(info.sub_graphs.is_empty()); // No subgraphs
+    assert!(info.clustering_coefficients().is_empty()); // No clustering coefficients
+    assert!(info.sub_graphs().is_empty()); // No subgraphs
+    assert!(info.strong_sub_graphs().is_empty()); // No strongly connected subgraphs
+}
+
+#[test]
+fn test_get_info_is_lazy() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 2.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+    let info = GraphInfo::get_info(&graph);
+
+    // Nothing but the degrees, trust scores and components yet
+    assert!(info.closeness_in.get().is_none());
+    assert!(info.pagerank.get().is_none());
+    assert!(info.triangles.get().is_none());
+
+    // The unweighted clustering coefficients come with the triangles
+    info.clustering_coefficients();
+    assert!(info.triangles.get().is_some());
+    assert!(info.closeness_in.get().is_none());
 }
 
 #[test]
 fn test_analyze_clustering_centrality() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
-        Edge { from: 2, to: 3, weight: 2.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 2.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
     let info = GraphInfo::get_info(&graph);
@@ -278,7 +606,7 @@ fn test_analyze_clustering_centrality() {
 #[test]
 fn test_analyze_clustering_centrality_no_scores() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 0.5 },
+        Edge { from: 1, to: 2, weight: 0.5, timestamp: None },
     ];
     let graph = Graph::new(&edges);
     let info = GraphInfo::get_info(&graph);
@@ -291,9 +619,9 @@ fn test_analyze_clustering_centrality_no_scores() {
 #[test]
 fn test_find_k_representatives() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
-        Edge { from: 2, to: 3, weight: 2.0 },
-        Edge { from: 3, to: 1, weight: 3.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 2.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 3.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
     let info = GraphInfo::get_info(&graph);
@@ -301,13 +629,14 @@ fn test_find_k_representatives() {
     let result = info.find_k_representatives(2);
     assert!(result.contains("Selected Representatives"));
     assert!(result.contains("Average clustering coefficient of representatives"));
+    assert!(result.contains("Core numbers of representatives: [2, 2] (innermost core: 2-core with 3 nodes)"));
 }
 
 // Test request more representatives than nodes
 #[test]
 fn test_find_k_representatives_insufficient_nodes() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
     let info = GraphInfo::get_info(&graph);
@@ -326,7 +655,7 @@ fn test_find_min_max_typical() {
     values.insert(3, 5.0);
     values.insert(4, 15.0);
 
-    let (min, max) = GraphInfo::find_min_max(&values);
+    let (min, max) = GraphInfo::<Graph>::find_min_max(&values);
 
     assert_eq!(min, 5.0);
     assert_eq!(max, 20.0);
@@ -339,7 +668,7 @@ fn test_find_min_max_empty() {
 
     let values: HashMap<usize, f64> = HashMap::new();
 
-    let (min, max) = GraphInfo::find_min_max(&values);
+    let (min, max) = GraphInfo::<Graph>::find_min_max(&values);
 
     assert_eq!(min, 0.0); // Should return 0.0
     assert_eq!(max, 0.0); // Same
@@ -351,7 +680,7 @@ fn test_normalize_typical() {
     let min = 10.0;
     let max = 20.0;
 
-    let normalized = GraphInfo::normalize(value, min, max);
+    let normalized = GraphInfo::<Graph>::normalize(value, min, max);
 
     assert_eq!(normalized, 0.5); // (15 - 10) / (20 - 10) = 0.5
 }
@@ -362,7 +691,280 @@ fn test_normalize_min_equals_max() {
     let min = 10.0;
     let max = 10.0;
 
-    let normalized = GraphInfo::normalize(value, min, max);
+    let normalized = GraphInfo::<Graph>::normalize(value, min, max);
 
     assert_eq!(normalized, 0.0); // Should handle division by zero.
 }
+
+#[test]
+fn test_csr_info_matches_get_info() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 2.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 3.0, timestamp: None },
+        Edge { from: 4, to: 5, weight: -1.0, timestamp: None },
+    ];
+    let info = GraphInfo::get_info(&Graph::new(&edges));
+    let csr_info = GraphInfo::get_info_with(&CsrGraph::from_edges(&edges), ClusteringVariant::Unweighted);
+
+    assert_eq!(csr_info.graph.node_count(), info.graph.node_count());
+    assert_eq!(csr_info.nodes_indegree, info.nodes_indegree);
+    assert_eq!(csr_info.nodes_outdegree, info.nodes_outdegree);
+    assert_eq!(csr_info.clustering_coefficients(), info.clustering_coefficients());
+    assert_eq!(csr_info.trust_scores, info.trust_scores);
+    assert_eq!(csr_info.pagerank(), info.pagerank());
+    assert_eq!(csr_info.sub_graphs().len(), info.sub_graphs().len());
+    assert_eq!(csr_info.strong_component_sizes(), info.strong_component_sizes());
+
+    // The clustering variant is kept
+    let signed_info = GraphInfo::get_info_with(&CsrGraph::from_edges(&edges), ClusteringVariant::Signed);
+    assert_eq!(signed_info.clustering_variant, ClusteringVariant::Signed);
+    assert_eq!(*signed_info.clustering_coefficients(), info.graph.clustering_coefficients(ClusteringVariant::Signed));
+}
+
+#[test]
+fn test_strong_component_sizes() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 2.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 3.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 1.0, timestamp: None },
+    ];
+    let info = GraphInfo::get_info(&Graph::new(&edges));
+
+    assert_eq!(info.sub_graphs().len(), 1); // One weak component
+    assert_eq!(info.strong_component_sizes(), vec![3, 1]); // Node 4 can't reach back
+}
+
+#[test]
+fn test_component_of() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 2.0, timestamp: None },
+    ];
+    let info = GraphInfo::get_info(&Graph::new(&edges));
+
+    let component = info.component_of(4).unwrap();
+    assert!(info.sub_graphs()[component].contains(3));
+    assert_ne!(info.component_of(1), info.component_of(3));
+    assert_eq!(info.component_of(5), None);
+}
+
+#[test]
+fn test_sub_graph_diameters() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 2.0, timestamp: None },
+        Edge { from: 4, to: 5, weight: -1.0, timestamp: None },
+    ];
+    let info = GraphInfo::get_info(&Graph::new(&edges));
+
+    let component = info.component_of(1).unwrap();
+    assert_eq!(info.sub_graph_diameters().len(), info.sub_graphs().len());
+    assert_eq!(info.sub_graph_diameters()[component], Diameter { value: 2, exact: true });
+    assert_eq!(info.sub_graph_diameters()[info.component_of(4).unwrap()].value, 1);
+}
+
+#[test]
+fn test_centrality_measures() {
+    // 1 -> 2 -> 3, node 2 is the only node in the middle of a path
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 2.0, timestamp: None },
+    ];
+    let info = GraphInfo::get_info(&Graph::new(&edges));
+
+    assert_eq!(info.centrality(CentralityMeasure::InDegree), info.nodes_indegree);
+    assert_eq!(info.centrality(CentralityMeasure::OutDegree), info.nodes_outdegree);
+    let betweenness = info.centrality(CentralityMeasure::Betweenness(BetweennessOptions::default()));
+    assert_eq!(betweenness[&2], 1.0);
+    assert_eq!(betweenness[&1], 0.0);
+
+    // Asking again reuses the scores instead of running Brandes a second time
+    assert_eq!(info.centrality(CentralityMeasure::Betweenness(BetweennessOptions::default())), betweenness);
+    assert_eq!(info.computed_centralities.borrow().len(), 1);
+}
+
+#[test]
+fn test_analyze_with_betweenness() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 2.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 5.0, timestamp: None },
+    ];
+    let info = GraphInfo::get_info(&Graph::new(&edges));
+    let betweenness = CentralityMeasure::Betweenness(BetweennessOptions::default());
+
+    // Nodes 3 and 4 have trust >= 2, only node 3 is on a path between others
+    let result = info.analyze_clustering_centrality_with(2.0, -1.0, betweenness);
+    assert!(result.contains("Nodes with trust score >= 2: 2 nodes."));
+    assert!(result.contains("Percentage with high centrality: 50.00%."));
+
+    // Nodes 2 and 3 are the middle of the path
+    let result = info.find_k_representatives_with(2, betweenness);
+    assert!(result.contains("Selected Representatives: [2, 3]") || result.contains("Selected Representatives: [3, 2]"));
+}
+
+#[test]
+fn test_closeness_and_harmonic_in_info() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 5.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 5.0, timestamp: None },
+    ];
+    let info = GraphInfo::get_info(&Graph::new(&edges));
+
+    assert_eq!(info.harmonic(Direction::In)[&3], 1.5);
+    assert_eq!(info.harmonic(Direction::Out)[&3], 0.0);
+    assert_eq!(info.centrality(CentralityMeasure::Harmonic(Direction::Out))[&1], 1.5);
+    assert_eq!(info.centrality(CentralityMeasure::Closeness(Direction::In)), *info.closeness(Direction::In));
+
+    // Nodes 2 and 3 are rated 5, harmonic in-centrality 1 and 1.5
+    let result = info.analyze_clustering_centrality(5.0, -1.0);
+    assert!(result.contains("Average harmonic centrality (in / out): 1.25 / 0.50."));
+}
+
+#[test]
+fn test_pagerank_in_info() {
+    let edges = vec![
+        Edge { from: 1, to: 3, weight: 5.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 5.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: -5.0, timestamp: None },
+    ];
+    let info = GraphInfo::get_info(&Graph::new(&edges));
+
+    assert_eq!(info.pagerank().len(), 3);
+    assert!(info.pagerank()[&3] > info.pagerank()[&1]);
+    assert_eq!(info.centrality(CentralityMeasure::PageRank), *info.pagerank());
+}
+
+#[test]
+fn test_goodness_in_info() {
+    let edges = vec![
+        Edge { from: 1, to: 3, weight: 10.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 10.0, timestamp: None },
+        Edge { from: 1, to: 4, weight: -10.0, timestamp: None },
+    ];
+    let info = GraphInfo::get_info(&Graph::new(&edges));
+
+    assert!(info.fairness_goodness().report.converged);
+    assert_eq!(info.fairness_goodness().goodness.len(), info.trust_scores.len());
+    assert!(info.fairness_goodness().goodness[&3] > 0.0);
+    assert!(info.fairness_goodness().goodness[&4] < 0.0);
+    assert_eq!(info.fairness_goodness().fairness[&1], 1.0); // Both ratings agree with the consensus
+}
+
+#[test]
+fn test_spearman_correlation() {
+    let a: HashMap<usize, f64> = [(1, 1.0), (2, 2.0), (3, 3.0), (4, 4.0)].into_iter().collect();
+    let same_order: HashMap<usize, f64> = [(1, 10.0), (2, 20.0), (3, 25.0), (4, 100.0)].into_iter().collect();
+    let reversed: HashMap<usize, f64> = [(1, 4.0), (2, 3.0), (3, 2.0), (4, 1.0), (5, 0.0)].into_iter().collect();
+
+    assert!((spearman_correlation(&a, &same_order).unwrap() - 1.0).abs() < 1e-12);
+    assert!((spearman_correlation(&a, &reversed).unwrap() + 1.0).abs() < 1e-12); // 5 is ignored
+
+    let constant: HashMap<usize, f64> = [(1, 1.0), (2, 1.0)].into_iter().collect();
+    assert_eq!(spearman_correlation(&a, &constant), None);
+    assert_eq!(spearman_correlation(&a, &HashMap::new()), None);
+}
+
+#[test]
+fn test_ranks_with_ties() {
+    assert_eq!(ranks(&[3.0, 1.0, 3.0, 2.0]), vec![3.5, 1.0, 3.5, 2.0]);
+}
+
+#[test]
+fn test_find_k_representatives_by_authority() {
+    // 4 is rated by everybody else
+    let edges = vec![
+        Edge { from: 1, to: 4, weight: 5.0, timestamp: None },
+        Edge { from: 2, to: 4, weight: 5.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 5.0, timestamp: None },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+    ];
+    let info = GraphInfo::get_info(&Graph::new(&edges));
+
+    let authority = info.centrality(CentralityMeasure::Authority(HitsOptions::default()));
+    assert!(authority[&4] > authority[&2]);
+
+    let result = info.find_k_representatives_with(1, CentralityMeasure::Authority(HitsOptions::default()));
+    assert!(result.contains("Selected Representatives: [4]"));
+}
+
+#[test]
+fn test_analyze_status() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 5.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: -5.0, timestamp: None },
+        Edge { from: 1, to: 3, weight: 5.0, timestamp: None },
+    ];
+    let report = GraphInfo::get_info(&Graph::new(&edges)).analyze_status();
+
+    assert_eq!(report.types.iter().map(|stats| stats.count).sum::<usize>(), 3);
+    assert!(report.to_string().contains("Contexts consistent with status (generative)"));
+}
+
+#[test]
+fn test_innermost_core() {
+    // Triangle 1, 2, 3 with 4 hanging off 1
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 1.0, timestamp: None },
+        Edge { from: 4, to: 1, weight: 1.0, timestamp: None },
+    ];
+    let info = GraphInfo::get_info(&Graph::new(&edges));
+
+    assert_eq!(info.core_numbers()[&4], 1);
+    assert_eq!(info.innermost_core(), (2, 3));
+    assert_eq!(GraphInfo::get_info(&Graph::new(&[])).innermost_core(), (0, 0));
+}
+
+#[test]
+fn test_triangles_in_info() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 1.0, timestamp: None },
+    ];
+    let info = GraphInfo::get_info(&Graph::new(&edges));
+
+    assert_eq!(info.triangles().total_triangles, 1);
+    assert_eq!(info.triangles().transitivity, 1.0);
+    assert_eq!(info.triangles().average_clustering, 1.0);
+    assert_eq!(info.triangles().average_directed_clustering, 0.5); // Cycle is 1 of the 2 possible directed triangles
+}
+
+#[test]
+fn test_get_info_with_clustering_variant() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 10.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 10.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: -10.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    let info = GraphInfo::get_info(&graph);
+    assert_eq!(info.clustering_variant, ClusteringVariant::Unweighted);
+    assert_eq!(info.clustering_coefficients()[&1], 0.5);
+
+    let signed = GraphInfo::get_info_with(&graph, ClusteringVariant::Signed);
+    assert_eq!(signed.clustering_variant, ClusteringVariant::Signed);
+    assert_eq!(signed.clustering_coefficients()[&1], -1.0);
+}
+
+#[test]
+fn test_summarize_communities() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 4.0, timestamp: None },
+        Edge { from: 2, to: 1, weight: 2.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 6.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+    let info = GraphInfo::get_info(&graph);
+    let partition = louvain(&graph, &LouvainOptions::default());
+
+    let summaries = info.summarize_communities(&partition);
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[0], CommunitySummary { size: 2, average_trust: 3.0, average_clustering: 0.0 });
+    assert_eq!(summaries[1].average_trust, 3.0); // 3 has no ratings, 4 has one 6
+}
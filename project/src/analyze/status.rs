@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::graph::Network;
+#[cfg(test)]
+use crate::graph::{Edge, Graph};
+
+// How a common neighbour X is linked to the two ends of a rating A -> B.
+// With the direction and sign of both links there are 16 contexts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusContext {
+    pub a_rates_x: bool,        // A -> X, otherwise X -> A
+    pub a_x_positive: bool,
+    pub x_rates_b: bool,        // X -> B, otherwise B -> X
+    pub x_b_positive: bool,
+}
+
+impl StatusContext {
+    fn index(&self) -> usize {
+        (self.a_rates_x as usize) << 3 | (self.a_x_positive as usize) << 2
+            | (self.x_rates_b as usize) << 1 | self.x_b_positive as usize
+    }
+
+    fn from_index(index: usize) -> StatusContext {
+        StatusContext {
+            a_rates_x: index & 8 != 0,
+            a_x_positive: index & 4 != 0,
+            x_rates_b: index & 2 != 0,
+            x_b_positive: index & 1 != 0,
+        }
+    }
+
+    // Sign of A -> B that status theory predicts from A's side (generative):
+    // X +> B or B -> X means B is above X, and A rates high status users positively.
+    pub fn status_generative(&self) -> bool {
+        self.x_rates_b == self.x_b_positive
+    }
+
+    // Sign of A -> B that status theory predicts from B's side (receptive):
+    // A +> X or X -> A means A is below X, and low status users rate positively.
+    pub fn status_receptive(&self) -> bool {
+        self.a_rates_x == self.a_x_positive
+    }
+
+    // Sign of A -> B that balance theory predicts for the triangle.
+    pub fn balance(&self) -> bool {
+        self.a_x_positive == self.x_b_positive
+    }
+}
+
+impl fmt::Display for StatusContext {
+    // e.g. "A+>X, B->X" for A rates X positively and B rates X negatively
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = |positive: bool| if positive { '+' } else { '-' };
+        let (a_side, b_side) = (sign(self.a_x_positive), sign(self.x_b_positive));
+        match self.a_rates_x {
+            true => write!(f, "A{}>X, ", a_side)?,
+            false => write!(f, "X{}>A, ", a_side)?,
+        }
+        match self.x_rates_b {
+            true => write!(f, "X{}>B", b_side),
+            false => write!(f, "B{}>X", b_side),
+        }
+    }
+}
+
+// Ratings A -> B seen in one context, compared to the null model where
+// every rating has the sign probability of its rater (generative) or of the
+// rated user (receptive). A surprise is the number of positive ratings above
+// what the null model expects, in standard deviations.
+#[derive(Debug, Clone)]
+pub struct StatusTriadStats {
+    pub context: StatusContext,
+    pub count: usize,
+    pub positive: usize,
+    pub generative_surprise: f64,
+    pub receptive_surprise: f64,
+}
+
+impl StatusTriadStats {
+    // Whether the generative surprise has the sign status theory predicts.
+    pub fn status_consistent_generative(&self) -> bool {
+        surprise_matches(self.generative_surprise, self.context.status_generative())
+    }
+
+    // Whether the receptive surprise has the sign status theory predicts.
+    pub fn status_consistent_receptive(&self) -> bool {
+        surprise_matches(self.receptive_surprise, self.context.status_receptive())
+    }
+
+    // Whether the generative surprise has the sign balance theory predicts.
+    pub fn balance_consistent(&self) -> bool {
+        surprise_matches(self.generative_surprise, self.context.balance())
+    }
+}
+
+// Status theory analysis of Leskovec et al. (CHI 2010) on every
+// contextualized rating, one entry per context.
+#[derive(Debug, Clone)]
+pub struct StatusReport {
+    pub types: Vec<StatusTriadStats>,
+}
+
+impl StatusReport {
+    // Number of contexts that agree with status theory on the generative side.
+    pub fn generative_consistency(&self) -> usize {
+        self.types.iter().filter(|stats| stats.status_consistent_generative()).count()
+    }
+
+    // Number of contexts that agree with status theory on the receptive side.
+    pub fn receptive_consistency(&self) -> usize {
+        self.types.iter().filter(|stats| stats.status_consistent_receptive()).count()
+    }
+
+    // Number of contexts that agree with balance theory, for comparison.
+    pub fn balance_consistency(&self) -> usize {
+        self.types.iter().filter(|stats| stats.balance_consistent()).count()
+    }
+}
+
+impl fmt::Display for StatusReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = |positive: bool| if positive { '+' } else { '-' };
+        writeln!(f, "{:<12} {:>7} {:>6} {:>10} {:>10}  status  balance", "Context", "Count", "P(+)", "Gen. surp.", "Rec. surp.")?;
+        for stats in &self.types {
+            let positive_fraction = if stats.count == 0 { 0.0 } else { stats.positive as f64 / stats.count as f64 };
+            writeln!(
+                f,
+                "{:<12} {:>7} {:>6.3} {:>10.2} {:>10.2}  {} / {}   {}",
+                stats.context.to_string(),
+                stats.count,
+                positive_fraction,
+                stats.generative_surprise,
+                stats.receptive_surprise,
+                sign(stats.context.status_generative()),
+                sign(stats.context.status_receptive()),
+                sign(stats.context.balance()),
+            )?;
+        }
+        writeln!(f, "\nContexts consistent with status (generative): {} of {}", self.generative_consistency(), self.types.len())?;
+        writeln!(f, "Contexts consistent with status (receptive): {} of {}", self.receptive_consistency(), self.types.len())?;
+        write!(f, "Contexts consistent with balance (generative): {} of {}", self.balance_consistency(), self.types.len())
+    }
+}
+
+// Go through every rating A -> B and every common neighbour X of A and B,
+// and gather the surprise of each of the 16 contexts. Ratings of 0 and
+// self ratings are left out.
+pub fn status_report<G: Network>(graph: &G) -> StatusReport {
+    // Sign of every rating, and how many positive ratings each node gives / gets
+    let mut signs: HashMap<(usize, usize), bool> = HashMap::new();
+    let mut given: HashMap<usize, (usize, usize)> = HashMap::new();      // (positive, total)
+    let mut received: HashMap<usize, (usize, usize)> = HashMap::new();
+    for edge in graph.edges() {
+        if edge.from == edge.to || edge.weight == 0.0 {
+            continue;
+        }
+        let positive = edge.weight > 0.0;
+        signs.insert((edge.from, edge.to), positive);
+        let rater = given.entry(edge.from).or_insert((0, 0));
+        rater.0 += positive as usize;
+        rater.1 += 1;
+        let rated = received.entry(edge.to).or_insert((0, 0));
+        rated.0 += positive as usize;
+        rated.1 += 1;
+    }
+    let fraction = |(positive, total): (usize, usize)| positive as f64 / total as f64;
+
+    let neighbors: HashMap<usize, HashSet<usize>> = graph.nodes().map(|node| (node, graph.neighbor_set(node)))
+        .collect();
+
+    // Per context: count, positive, and expected positives and variance of both null models
+    let mut counts = [(0usize, 0usize); 16];
+    let mut generative = [(0.0, 0.0); 16];
+    let mut receptive = [(0.0, 0.0); 16];
+
+    for (&(a, b), &positive) in &signs {
+        let generative_probability = fraction(given[&a]);
+        let receptive_probability = fraction(received[&b]);
+
+        for &x in neighbors[&a].intersection(&neighbors[&b]) {
+            if x == a || x == b {
+                continue;
+            }
+            for (a_rates_x, a_x) in [(true, signs.get(&(a, x))), (false, signs.get(&(x, a)))] {
+                for (x_rates_b, x_b) in [(true, signs.get(&(x, b))), (false, signs.get(&(b, x)))] {
+                    let (Some(&a_x_positive), Some(&x_b_positive)) = (a_x, x_b) else {
+                        continue;
+                    };
+                    let index = StatusContext { a_rates_x, a_x_positive, x_rates_b, x_b_positive }.index();
+
+                    counts[index].0 += 1;
+                    counts[index].1 += positive as usize;
+                    generative[index].0 += generative_probability;
+                    generative[index].1 += generative_probability * (1.0 - generative_probability);
+                    receptive[index].0 += receptive_probability;
+                    receptive[index].1 += receptive_probability * (1.0 - receptive_probability);
+                }
+            }
+        }
+    }
+
+    let types = (0..16)
+        .map(|index| StatusTriadStats {
+            context: StatusContext::from_index(index),
+            count: counts[index].0,
+            positive: counts[index].1,
+            generative_surprise: surprise(counts[index].1, generative[index]),
+            receptive_surprise: surprise(counts[index].1, receptive[index]),
+        })
+        .collect();
+
+    StatusReport { types }
+}
+
+// helper that gives how many standard deviations the observed positives are
+// above the expected ones, 0 if the null model has no variance
+fn surprise(positive: usize, (expected, variance): (f64, f64)) -> f64 {
+    if variance == 0.0 {
+        0.0
+    } else {
+        (positive as f64 - expected) / variance.sqrt()
+    }
+}
+
+// helper that checks a surprise has the predicted sign (a surprise of 0 agrees with nothing)
+fn surprise_matches(surprise: f64, predicted_positive: bool) -> bool {
+    if predicted_positive { surprise > 0.0 } else { surprise < 0.0 }
+}
+
+// ----------------------- TESTS -----------------------
+
+#[test]
+fn test_status_context_index() {
+    for index in 0..16 {
+        assert_eq!(StatusContext::from_index(index).index(), index);
+    }
+    let context = StatusContext { a_rates_x: true, a_x_positive: true, x_rates_b: false, x_b_positive: false };
+    assert_eq!(context.to_string(), "A+>X, B->X");
+    assert!(context.status_generative()); // B distrusts X so B is above X
+    assert!(context.status_receptive()); // A trusts X so A is below X
+    assert!(!context.balance());
+}
+
+#[test]
+fn test_status_report() {
+    // 1 trusts 2 and 3 but not 4, and 2 trusts 3
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 5.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 5.0, timestamp: None },
+        Edge { from: 1, to: 3, weight: 5.0, timestamp: None },
+        Edge { from: 1, to: 4, weight: -5.0, timestamp: None },
+    ];
+    let report = status_report(&Graph::new(&edges));
+
+    assert_eq!(report.types.len(), 16);
+    assert_eq!(report.types.iter().map(|stats| stats.count).sum::<usize>(), 3); // One context per side of the triangle
+
+    // 1 -> 3 in the context 1 +> 2 +> 3, while 1 only gives 2 of 3 ratings positive
+    let chain = StatusContext { a_rates_x: true, a_x_positive: true, x_rates_b: true, x_b_positive: true };
+    let stats = &report.types[chain.index()];
+    assert_eq!((stats.count, stats.positive), (1, 1));
+    assert!((stats.generative_surprise - (1.0 / 3.0) / (2.0f64 / 9.0).sqrt()).abs() < 1e-9);
+    assert_eq!(stats.receptive_surprise, 0.0); // 3 only gets positive ratings
+    assert!(stats.status_consistent_generative());
+    assert!(!stats.status_consistent_receptive());
+}
@@ -0,0 +1,315 @@
+use project::analyze::CentralityMeasure;
+use project::graph::ClusteringVariant;
+use project::graph::algorithm::{BetweennessOptions, Direction, HitsOptions};
+use std::fmt;
+
+// Which part of the analysis to print. All prints every section like
+// the original main did.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    All,
+    Info,
+    Components,
+    Communities,
+    Centrality,
+    Representatives,
+    Trust,
+    Balance,
+    Path,
+}
+
+impl Command {
+    fn from_name(name: &str) -> Option<Command> {
+        match name {
+            "all" => Some(Command::All),
+            "info" => Some(Command::Info),
+            "components" => Some(Command::Components),
+            "communities" => Some(Command::Communities),
+            "centrality" => Some(Command::Centrality),
+            "representatives" => Some(Command::Representatives),
+            "trust" => Some(Command::Trust),
+            "balance" => Some(Command::Balance),
+            "path" => Some(Command::Path),
+            _ => None,
+        }
+    }
+}
+
+// Parsed command line. Defaults are the values main used to hardcode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliArgs {
+    pub command: Command,
+    pub input: String,          // Path of the edge list
+    pub high_score: f64,        // Nodes with trust score >= this are "high trust"
+    pub low_score: f64,         // Nodes with trust score <= this are "low trust"
+    pub k: usize,               // Number of representatives
+    pub lenient: bool,          // Skip malformed rows instead of stopping
+    pub centrality: Option<CentralityMeasure>,  // None uses each section's default
+    pub clustering: ClusteringVariant,
+    pub seeds: Vec<usize>,      // Known good users for personalized PageRank and EigenTrust
+    pub from: Option<usize>,    // Start node of the path command
+    pub to: Option<usize>,      // End node of the path command
+}
+
+impl Default for CliArgs {
+    fn default() -> CliArgs {
+        CliArgs {
+            command: Command::All,
+            input: "soc-sign-bitcoinalpha.csv".to_string(),
+            high_score: 4.0,
+            low_score: -2.0,
+            k: 15,
+            lenient: false,
+            centrality: None,
+            clustering: ClusteringVariant::Unweighted,
+            seeds: Vec::new(),
+            from: None,
+            to: None,
+        }
+    }
+}
+
+// Errors from parsing the command line. Help is not really an error but
+// stops the run the same way.
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    Help,
+    UnknownCommand(String),
+    UnknownFlag(String),
+    MissingValue(String),
+    InvalidValue { flag: String, value: String },
+    Requires { flag: String, requirement: String },
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Help => write!(f, "{}", usage()),
+            CliError::UnknownCommand(command) => write!(f, "unknown command \"{}\"\n\n{}", command, usage()),
+            CliError::UnknownFlag(flag) => write!(f, "unknown flag \"{}\"\n\n{}", flag, usage()),
+            CliError::MissingValue(flag) => write!(f, "missing value for {}", flag),
+            CliError::InvalidValue { flag, value } => write!(f, "invalid value \"{}\" for {}", value, flag),
+            CliError::Requires { flag, requirement } => write!(f, "{} only works with {}", flag, requirement),
+        }
+    }
+}
+
+pub fn usage() -> String {
+    "Usage: project [COMMAND] [OPTIONS]
+
+Commands:
+  all               Print every section (default)
+  info              General info about the graph
+  components        Sizes of the connected sub graphs
+  communities       Louvain communities of the positive ratings and of the signed graph
+  centrality        Clustering and centrality of high / low trust nodes
+  representatives   The k most representative nodes
+  trust             Trust score summary
+  balance           Structural balance, triad census and status theory of signed triads
+  path              Cheapest path through positive ratings (needs --from and --to)
+
+Options:
+  -i, --input <PATH>    Edge list to load [default: soc-sign-bitcoinalpha.csv]
+      --high <SCORE>    High trust threshold [default: 4]
+      --low <SCORE>     Low trust threshold [default: -2]
+  -k, --k <K>           Number of representatives [default: 15]
+      --centrality <MEASURE>
+                        in-degree, out-degree, betweenness, weighted-betweenness,
+                        closeness-in, closeness-out, harmonic-in, harmonic-out, pagerank,
+                        authority or positive-authority (HITS)
+                        [default: out-degree for centrality, in-degree for representatives]
+      --clustering <VARIANT>
+                        unweighted, onnela, barrat or signed [default: unweighted]
+      --samples <K>     Approximate betweenness from K sampled source nodes
+      --seeds <NODES>   Comma separated known good users, the trust command
+                        then shows personalized PageRank from them and uses
+                        them as pre-trusted peers for EigenTrust
+      --from <NODE>     Start node of the path command
+      --to <NODE>       End node of the path command
+      --lenient         Skip malformed rows instead of stopping
+  -h, --help            Print this message"
+        .to_string()
+}
+
+// Parse the arguments after the program name.
+pub fn parse_args(args: &[String]) -> Result<CliArgs, CliError> {
+    let mut cli_args = CliArgs::default();
+    let mut command: Option<Command> = None;
+    let mut samples: Option<usize> = None;
+    let mut args_iter = args.iter();
+
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Err(CliError::Help),
+            "--lenient" => cli_args.lenient = true,
+            "-i" | "--input" => cli_args.input = flag_value(arg, args_iter.next())?.to_string(),
+            "--high" => cli_args.high_score = parse_value(arg, args_iter.next())?,
+            "--low" => cli_args.low_score = parse_value(arg, args_iter.next())?,
+            "-k" | "--k" => cli_args.k = parse_value(arg, args_iter.next())?,
+            "--centrality" => cli_args.centrality = Some(parse_centrality(arg, args_iter.next())?),
+            "--clustering" => cli_args.clustering = parse_clustering(arg, args_iter.next())?,
+            "--samples" => samples = Some(parse_value(arg, args_iter.next())?),
+            "--seeds" => cli_args.seeds = parse_list(arg, args_iter.next())?,
+            "--from" => cli_args.from = Some(parse_value(arg, args_iter.next())?),
+            "--to" => cli_args.to = Some(parse_value(arg, args_iter.next())?),
+            flag if flag.starts_with('-') => return Err(CliError::UnknownFlag(flag.to_string())),
+            name => {
+                if command.is_some() {
+                    return Err(CliError::UnknownCommand(name.to_string()));
+                }
+                command = Some(Command::from_name(name).ok_or_else(|| CliError::UnknownCommand(name.to_string()))?);
+            }
+        }
+    }
+
+    cli_args.command = command.unwrap_or(Command::All);
+    match cli_args.centrality.as_mut() {
+        Some(CentralityMeasure::Betweenness(options)) => options.samples = samples,
+        _ if samples.is_some() => {
+            let requirement = "--centrality betweenness or weighted-betweenness".to_string();
+            return Err(CliError::Requires { flag: "--samples".to_string(), requirement });
+        }
+        _ => {}
+    }
+    if cli_args.command == Command::Path {
+        if cli_args.from.is_none() {
+            return Err(CliError::MissingValue("--from".to_string()));
+        }
+        if cli_args.to.is_none() {
+            return Err(CliError::MissingValue("--to".to_string()));
+        }
+    }
+    Ok(cli_args)
+}
+
+// helper that parses the name of a centrality measure.
+fn parse_centrality(flag: &str, value: Option<&String>) -> Result<CentralityMeasure, CliError> {
+    match flag_value(flag, value)? {
+        "in-degree" => Ok(CentralityMeasure::InDegree),
+        "out-degree" => Ok(CentralityMeasure::OutDegree),
+        "betweenness" => Ok(CentralityMeasure::Betweenness(BetweennessOptions::default())),
+        "weighted-betweenness" => Ok(CentralityMeasure::Betweenness(BetweennessOptions { weighted: true, ..Default::default() })),
+        "closeness-in" => Ok(CentralityMeasure::Closeness(Direction::In)),
+        "closeness-out" => Ok(CentralityMeasure::Closeness(Direction::Out)),
+        "harmonic-in" => Ok(CentralityMeasure::Harmonic(Direction::In)),
+        "harmonic-out" => Ok(CentralityMeasure::Harmonic(Direction::Out)),
+        "pagerank" => Ok(CentralityMeasure::PageRank),
+        "authority" => Ok(CentralityMeasure::Authority(HitsOptions::default())),
+        "positive-authority" => Ok(CentralityMeasure::Authority(HitsOptions { positive_only: true, ..Default::default() })),
+        other => Err(CliError::InvalidValue { flag: flag.to_string(), value: other.to_string() }),
+    }
+}
+
+// helper that parses the name of a clustering coefficient variant.
+fn parse_clustering(flag: &str, value: Option<&String>) -> Result<ClusteringVariant, CliError> {
+    match flag_value(flag, value)? {
+        "unweighted" => Ok(ClusteringVariant::Unweighted),
+        "onnela" => Ok(ClusteringVariant::Onnela),
+        "barrat" => Ok(ClusteringVariant::Barrat),
+        "signed" => Ok(ClusteringVariant::Signed),
+        other => Err(CliError::InvalidValue { flag: flag.to_string(), value: other.to_string() }),
+    }
+}
+
+// helper that parses a comma separated list following a flag.
+fn parse_list<T: std::str::FromStr>(flag: &str, value: Option<&String>) -> Result<Vec<T>, CliError> {
+    let value = flag_value(flag, value)?;
+    value.split(',')
+        .map(|item| item.trim().parse().map_err(|_| CliError::InvalidValue { flag: flag.to_string(), value: value.to_string() }))
+        .collect()
+}
+
+// helper that returns the value following a flag.
+fn flag_value<'a>(flag: &str, value: Option<&'a String>) -> Result<&'a str, CliError> {
+    value.map(|value| value.as_str()).ok_or_else(|| CliError::MissingValue(flag.to_string()))
+}
+
+// helper that parses the value following a flag.
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<&String>) -> Result<T, CliError> {
+    let value = flag_value(flag, value)?;
+    value.parse().map_err(|_| CliError::InvalidValue { flag: flag.to_string(), value: value.to_string() })
+}
+
+// ----------------------- TESTS ----------------------- 
+
+#[cfg(test)]
+fn to_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn test_parse_args_defaults() {
+    let cli_args = parse_args(&[]).unwrap();
+
+    assert_eq!(cli_args, CliArgs::default()); // Same values main used to hardcode
+    assert_eq!(cli_args.command, Command::All);
+}
+
+#[test]
+fn test_parse_args_subcommand_and_flags() {
+    let args = to_args(&["centrality", "--input", "other.csv", "--high", "5", "--low", "-3.5", "-k", "20", "--lenient"]);
+    let cli_args = parse_args(&args).unwrap();
+
+    assert_eq!(cli_args.command, Command::Centrality);
+    assert_eq!(cli_args.input, "other.csv");
+    assert_eq!(cli_args.high_score, 5.0);
+    assert_eq!(cli_args.low_score, -3.5); // Negative numbers are values, not flags
+    assert_eq!(cli_args.k, 20);
+    assert!(cli_args.lenient);
+}
+
+#[test]
+fn test_parse_args_errors() {
+    assert_eq!(parse_args(&to_args(&["plot"])), Err(CliError::UnknownCommand("plot".to_string())));
+    assert_eq!(parse_args(&to_args(&["--verbose"])), Err(CliError::UnknownFlag("--verbose".to_string())));
+    assert_eq!(parse_args(&to_args(&["-k"])), Err(CliError::MissingValue("-k".to_string())));
+    assert_eq!(
+        parse_args(&to_args(&["-k", "many"])),
+        Err(CliError::InvalidValue { flag: "-k".to_string(), value: "many".to_string() })
+    );
+    assert_eq!(parse_args(&to_args(&["info", "trust"])), Err(CliError::UnknownCommand("trust".to_string())));
+}
+
+#[test]
+fn test_parse_args_path() {
+    let cli_args = parse_args(&to_args(&["path", "--from", "7188", "--to", "1"])).unwrap();
+
+    assert_eq!(cli_args.command, Command::Path);
+    assert_eq!((cli_args.from, cli_args.to), (Some(7188), Some(1)));
+    assert_eq!(parse_args(&to_args(&["path", "--from", "1"])), Err(CliError::MissingValue("--to".to_string())));
+}
+
+#[test]
+fn test_parse_args_centrality() {
+    let cli_args = parse_args(&to_args(&["representatives", "--centrality", "weighted-betweenness", "--samples", "100"])).unwrap();
+    let expected = BetweennessOptions { weighted: true, samples: Some(100), ..Default::default() };
+    assert_eq!(cli_args.centrality, Some(CentralityMeasure::Betweenness(expected)));
+
+    let cli_args = parse_args(&to_args(&["--centrality", "in-degree"])).unwrap();
+    assert_eq!(cli_args.centrality, Some(CentralityMeasure::InDegree));
+    let cli_args = parse_args(&to_args(&["--centrality", "harmonic-in"])).unwrap();
+    assert_eq!(cli_args.centrality, Some(CentralityMeasure::Harmonic(Direction::In)));
+    let cli_args = parse_args(&to_args(&["--centrality", "positive-authority"])).unwrap();
+    assert_eq!(cli_args.centrality, Some(CentralityMeasure::Authority(HitsOptions { positive_only: true, ..Default::default() })));
+    assert!(matches!(parse_args(&to_args(&["--centrality", "pagerank-ish"])), Err(CliError::InvalidValue { .. })));
+
+    // Sampling only applies to betweenness
+    assert!(matches!(parse_args(&to_args(&["--centrality", "pagerank", "--samples", "100"])), Err(CliError::Requires { .. })));
+    assert!(matches!(parse_args(&to_args(&["--samples", "100"])), Err(CliError::Requires { .. })));
+}
+
+#[test]
+fn test_parse_args_seeds() {
+    let cli_args = parse_args(&to_args(&["trust", "--seeds", "1, 7188,430"])).unwrap();
+    assert_eq!(cli_args.seeds, vec![1, 7188, 430]);
+
+    assert!(matches!(parse_args(&to_args(&["--seeds", "1,x"])), Err(CliError::InvalidValue { .. })));
+}
+
+#[test]
+fn test_parse_args_clustering() {
+    assert_eq!(parse_args(&to_args(&[])).unwrap().clustering, ClusteringVariant::Unweighted);
+    let cli_args = parse_args(&to_args(&["info", "--clustering", "barrat"])).unwrap();
+    assert_eq!(cli_args.clustering, ClusteringVariant::Barrat);
+    assert!(matches!(parse_args(&to_args(&["--clustering", "weighted"])), Err(CliError::InvalidValue { .. })));
+}
@@ -1,33 +1,339 @@
 use csv::ReaderBuilder;
+use csv::StringRecord;
+use flate2::read::MultiGzDecoder;
+use std::fmt;
 use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Read};
+#[cfg(test)]
 use std::collections::HashSet;
 
 use crate::graph::Edge;
 
+// Errors that can happen while loading an edge list.
+// Every row level error carries the line number in the file and the raw record
+// so the bad row can be found without re-reading the whole file.
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),                                                      // File could not be opened or read
+    MissingColumn { line: u64, column: usize, record: String },         // Row has fewer columns than needed
+    InvalidNodeId { line: u64, value: String, record: String },         // Source or target is not a node id
+    InvalidWeight { line: u64, value: String, record: String },         // Weight is not a number
+    InvalidTimestamp { line: u64, value: String, record: String },      // Timestamp is not an integer
+    Csv { line: u64, error: csv::Error },                               // Row the csv reader couldn't read, e.g. invalid UTF-8
+    UnknownColumn { name: String },                                     // Named column is not in the header row
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "I/O error: {}", err),
+            LoadError::MissingColumn { line, column, record } => {
+                write!(f, "line {}: missing column {} in record \"{}\"", line, column, record)
+            }
+            LoadError::InvalidNodeId { line, value, record } => {
+                write!(f, "line {}: invalid node id \"{}\" in record \"{}\"", line, value, record)
+            }
+            LoadError::InvalidWeight { line, value, record } => {
+                write!(f, "line {}: invalid weight \"{}\" in record \"{}\"", line, value, record)
+            }
+            LoadError::InvalidTimestamp { line, value, record } => {
+                write!(f, "line {}: invalid timestamp \"{}\" in record \"{}\"", line, value, record)
+            }
+            LoadError::Csv { line, error } => write!(f, "line {}: {}", line, error),
+            LoadError::UnknownColumn { name } => write!(f, "no column named \"{}\" in the header row", name),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Io(err) => Some(err),
+            LoadError::Csv { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> LoadError {
+        LoadError::Io(err)
+    }
+}
+
+// The csv reader uses the same error type for I/O failures and for rows it
+// can't read, only the latter are row errors.
+impl From<csv::Error> for LoadError {
+    fn from(err: csv::Error) -> LoadError {
+        if err.is_io_error() {
+            return LoadError::Io(err.into());
+        }
+        let line = err.position().map(|position| position.line()).unwrap_or(0);
+        LoadError::Csv { line, error: err }
+    }
+}
+
+// Result of a lenient load: the edges that could be parsed and
+// the errors of every row that was skipped.
+#[derive(Debug)]
+pub struct LoadReport {
+    pub edges: Vec<Edge>,
+    pub skipped: Vec<LoadError>,
+}
+
+// A column of the input, either by its position or by its name in the header row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+impl From<usize> for Column {
+    fn from(index: usize) -> Column {
+        Column::Index(index)
+    }
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Column {
+        Column::Name(name.to_string())
+    }
+}
+
+// First two bytes of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// Open the input file, decompressing it on the fly if it is gzip.
+// Gzip is detected by the .gz extension or by the magic bytes, so the
+// SNAP archives (e.g. soc-sign-bitcoinalpha.csv.gz) can be loaded directly.
+fn open_input(file_path: &str) -> Result<Box<dyn Read>, LoadError> {
+    let mut reader = BufReader::new(File::open(file_path)?);
+
+    let is_gzip = file_path.ends_with(".gz") || reader.fill_buf()?.starts_with(&GZIP_MAGIC);
+    if is_gzip {
+        Ok(Box::new(MultiGzDecoder::new(reader)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+// Builder describing the layout of an edge list file.
+// The default matches soc-sign-bitcoinalpha.csv: comma separated, no header,
+// columns from, to, weight, timestamp.
+//
+// e.g. a SNAP .txt edge list with '#' comments and no weights column:
+// LoaderOptions::new().delimiter(b'\t').comment(b'#').no_weight().load("edges.txt")
+#[derive(Debug, Clone)]
+pub struct LoaderOptions {
+    delimiter: u8,
+    has_headers: bool,
+    comment: Option<u8>,
+    source: Column,
+    target: Column,
+    weight: Option<Column>,
+    timestamp: Option<Column>,
+}
+
+impl Default for LoaderOptions {
+    fn default() -> LoaderOptions {
+        LoaderOptions {
+            delimiter: b',',
+            has_headers: false,
+            comment: None,
+            source: Column::Index(0),
+            target: Column::Index(1),
+            weight: Some(Column::Index(2)),
+            timestamp: Some(Column::Index(3)),
+        }
+    }
+}
+
+impl LoaderOptions {
+    pub fn new() -> LoaderOptions {
+        LoaderOptions::default()
+    }
+
+    // Field separator, e.g. b',' or b'\t'.
+    pub fn delimiter(mut self, delimiter: u8) -> LoaderOptions {
+        self.delimiter = delimiter;
+        self
+    }
+
+    // Whether the first row is a header. Needed to select columns by name.
+    pub fn has_headers(mut self, has_headers: bool) -> LoaderOptions {
+        self.has_headers = has_headers;
+        self
+    }
+
+    // Lines starting with this byte are ignored, e.g. b'#' for SNAP files.
+    pub fn comment(mut self, prefix: u8) -> LoaderOptions {
+        self.comment = Some(prefix);
+        self
+    }
+
+    pub fn source_column(mut self, column: impl Into<Column>) -> LoaderOptions {
+        self.source = column.into();
+        self
+    }
+
+    pub fn target_column(mut self, column: impl Into<Column>) -> LoaderOptions {
+        self.target = column.into();
+        self
+    }
+
+    pub fn weight_column(mut self, column: impl Into<Column>) -> LoaderOptions {
+        self.weight = Some(column.into());
+        self
+    }
+
+    // The file has no weights, every edge gets weight 1.0.
+    pub fn no_weight(mut self) -> LoaderOptions {
+        self.weight = None;
+        self
+    }
+
+    // Rows where this column is absent get no timestamp.
+    pub fn timestamp_column(mut self, column: impl Into<Column>) -> LoaderOptions {
+        self.timestamp = Some(column.into());
+        self
+    }
+
+    // Ignore timestamps entirely.
+    pub fn no_timestamp(mut self) -> LoaderOptions {
+        self.timestamp = None;
+        self
+    }
+
+    // Load the file and stop at the first bad row.
+    pub fn load(&self, file_path: &str) -> Result<Vec<Edge>, LoadError> {
+        Ok(self.load_edges(file_path, false)?.edges)
+    }
+
+    // Load the file, skipping bad rows and returning them in the report.
+    // I/O errors, unknown column names and a bad header row are still returned as Err.
+    pub fn load_lenient(&self, file_path: &str) -> Result<LoadReport, LoadError> {
+        self.load_edges(file_path, true)
+    }
+
+    // Shared loading loop. In lenient mode row errors are collected,
+    // otherwise the first one is returned.
+    fn load_edges(&self, file_path: &str, lenient: bool) -> Result<LoadReport, LoadError> {
+        let input = open_input(file_path)?;
+        let mut reader = ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(self.has_headers)
+            .comment(self.comment)
+            .flexible(true)
+            .from_reader(input);
+
+        // Turn the columns into positions once, using the header row for names.
+        let headers: Option<StringRecord> = if self.has_headers {
+            Some(reader.headers()?.clone())
+        } else {
+            None
+        };
+        let layout = ColumnLayout {
+            source: Self::resolve(&self.source, headers.as_ref())?,
+            target: Self::resolve(&self.target, headers.as_ref())?,
+            weight: match &self.weight {
+                Some(column) => Some(Self::resolve(column, headers.as_ref())?),
+                None => None,
+            },
+            timestamp: match &self.timestamp {
+                Some(column) => Some(Self::resolve(column, headers.as_ref())?),
+                None => None,
+            },
+        };
+
+        let mut edges = Vec::new();
+        let mut skipped = Vec::new();
+
+        for line in reader.records() {
+            let parsed = line.map_err(LoadError::from)
+                .and_then(|line_record| parse_record(&line_record, &layout, self.delimiter));
+
+            match parsed {
+                Ok(edge) => edges.push(edge),
+                Err(LoadError::Io(err)) => return Err(LoadError::Io(err)),
+                Err(err) if lenient => skipped.push(err),
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(LoadReport { edges, skipped })
+    }
+
+    // Position of a column, looking names up in the header row.
+    fn resolve(column: &Column, headers: Option<&StringRecord>) -> Result<usize, LoadError> {
+        match column {
+            Column::Index(index) => Ok(*index),
+            Column::Name(name) => headers
+                .and_then(|headers| headers.iter().position(|header| header.trim() == name))
+                .ok_or_else(|| LoadError::UnknownColumn { name: name.clone() }),
+        }
+    }
+}
+
+// Column positions after names have been resolved.
+struct ColumnLayout {
+    source: usize,
+    target: usize,
+    weight: Option<usize>,
+    timestamp: Option<usize>,
+}
+
 // function that read the csv_file and construct the datas in to a vector of Edge structs.
-pub fn read_csv(file_path: &str) -> Vec<Edge> {
+// Stops at the first bad row and returns its error.
+pub fn read_csv(file_path: &str) -> Result<Vec<Edge>, LoadError> {
     // file_path - by default, it should be "soc-sign-bitcoinalpha.csv" if you runs the project
     // at DS210Project/project.
+    LoaderOptions::default().load(file_path)
+}
 
-    let file = File::open(file_path).unwrap();
-    let mut reader = ReaderBuilder::new().has_headers(false).from_reader(file);
+// Same as read_csv but bad rows are skipped and returned in the report
+// instead of stopping the load. I/O errors are still returned as Err.
+pub fn read_csv_lenient(file_path: &str) -> Result<LoadReport, LoadError> {
+    LoaderOptions::default().load_lenient(file_path)
+}
 
-    let mut edges = Vec::new();
+// Parse one record to an Edge. The record in errors is joined with the
+// file's delimiter so it reads like the line in the file.
+fn parse_record(line_record: &StringRecord, layout: &ColumnLayout, delimiter: u8) -> Result<Edge, LoadError> {
+    let line = line_record.position().map(|position| position.line()).unwrap_or(0);
+    let record = line_record.iter().collect::<Vec<&str>>().join(&(delimiter as char).to_string());
 
-    for line in reader.records() {
-        let line_record = line.unwrap();
+    let field = |column: usize| -> Result<&str, LoadError> {
+        line_record.get(column).ok_or_else(|| LoadError::MissingColumn { line, column, record: record.clone() })
+    };
+    let node = |column: usize| -> Result<usize, LoadError> {
+        let value = field(column)?;
+        value.trim().parse().map_err(|_| LoadError::InvalidNodeId { line, value: value.to_string(), record: record.clone() })
+    };
 
-        // Parse each line to the corresponding four fields
-        let from: usize = line_record[0].parse().unwrap();    // The node that the edge comes from
-        let to: usize = line_record[1].parse().unwrap();      // The node that the edge points to
-        let weight: f64 = line_record[2].parse().unwrap();    // weight
+    // Parse each line to the corresponding four fields
+    let from: usize = node(layout.source)?;     // The node that the edge comes from
+    let to: usize = node(layout.target)?;       // The node that the edge points to
 
-        edges.push(Edge {from: from, to: to, weight: weight});
-    }
+    // weight, 1.0 if the file has no weights column
+    let weight: f64 = match layout.weight {
+        Some(column) => {
+            let weight_value = field(column)?;
+            // NaN and infinite weights parse as f64 but break every score computed from them
+            weight_value.trim().parse().ok().filter(|weight: &f64| weight.is_finite())
+                .ok_or_else(|| LoadError::InvalidWeight { line, value: weight_value.to_string(), record: record.clone() })?
+        }
+        None => 1.0,
+    };
 
-    let num_edges: usize = edges.len();
-    println!("\nTotal number of edges: {}\n", num_edges);
-    return edges;
+    // The timestamp is optional, some edge lists only have three columns.
+    let timestamp: Option<i64> = match layout.timestamp.and_then(|column| line_record.get(column)) {
+        Some(value) => Some(value.trim().parse()
+            .map_err(|_| LoadError::InvalidTimestamp { line, value: value.to_string(), record: record.clone() })?),
+        None => None,
+    };
+
+    Ok(Edge {from, to, weight, timestamp})
 }
 
 // ----------------------- TESTS ----------------------- 
@@ -35,7 +341,7 @@ pub fn read_csv(file_path: &str) -> Vec<Edge> {
 #[test]
 fn test_read_csv_num_edges() {
     let file = "soc-sign-bitcoinalpha.csv";
-    let edges = read_csv(file);
+    let edges = read_csv(file).unwrap();
 
     // Num edges should = 24186 according to data base's documentation
     assert_eq!(edges.len(), 24186, "The number of edges should be 24186");
@@ -44,7 +350,7 @@ fn test_read_csv_num_edges() {
 #[test]
 fn test_read_csv_num_nodes() {
     let file = "soc-sign-bitcoinalpha.csv";
-    let edges = read_csv(file);
+    let edges = read_csv(file).unwrap();
 
     let mut nodes = HashSet::new();
     for edge in &edges {
@@ -54,6 +360,176 @@ fn test_read_csv_num_nodes() {
 
     // Num nodes should = 3783 according to data base's documentation.
     assert_eq!(nodes.len(), 3783, "The number of nodes should be 3783");
+}
+
+#[test]
+fn test_read_csv_timestamps() {
+    let file = "soc-sign-bitcoinalpha.csv";
+    let edges = read_csv(file).unwrap();
+
+    // First row of the data is 7188,1,10,1407470400
+    assert_eq!(edges[0].timestamp, Some(1407470400));
+    assert!(edges.iter().all(|edge| edge.timestamp.is_some()));
+}
+
+// Path of a test file in the temp directory. The process id keeps parallel
+// and repeated test runs from writing the same file, every test uses its own name.
+#[cfg(test)]
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("{}_{}", std::process::id(), name))
+}
+
+// Write a small edge list to the temp directory for the error tests.
+#[cfg(test)]
+fn write_temp_csv(name: &str, contents: impl AsRef<[u8]>) -> String {
+    let path = temp_path(name);
+    std::fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_read_csv_missing_file() {
+    let result = read_csv("this-file-does-not-exist.csv");
+    assert!(matches!(result, Err(LoadError::Io(_))));
+}
+
+#[test]
+fn test_read_csv_invalid_node_id() {
+    let path = write_temp_csv("ds210_invalid_node.csv", "1,2,3,100\nabc,2,3,100\n");
+
+    match read_csv(&path) {
+        Err(LoadError::InvalidNodeId { line, value, record }) => {
+            assert_eq!(line, 2);
+            assert_eq!(value, "abc");
+            assert_eq!(record, "abc,2,3,100");
+        }
+        other => panic!("expected InvalidNodeId, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_csv_invalid_weight_and_missing_column() {
+    let path = write_temp_csv("ds210_invalid_weight.csv", "1,2,high,100\n");
+    assert!(matches!(read_csv(&path), Err(LoadError::InvalidWeight { line: 1, .. })));
+
+    let path = write_temp_csv("ds210_missing_column.csv", "1,2\n");
+    assert!(matches!(read_csv(&path), Err(LoadError::MissingColumn { line: 1, column: 2, .. })));
+}
+
+#[test]
+fn test_read_csv_non_finite_weight() {
+    let path = write_temp_csv("ds210_nan_weight.csv", "1,2,3,0\n1,2,NaN,0\n");
+    assert!(matches!(read_csv(&path), Err(LoadError::InvalidWeight { line: 2, .. })));
+
+    let path = write_temp_csv("ds210_inf_weight.csv", "1,2,-inf,0\n");
+    assert!(matches!(read_csv(&path), Err(LoadError::InvalidWeight { line: 1, .. })));
+}
+
+#[test]
+fn test_read_csv_lenient_skips_bad_rows() {
+    let path = write_temp_csv("ds210_lenient.csv", "1,2,3,100\n2,x,3,100\n3,1,-2\n4,1\n");
+    let report = read_csv_lenient(&path).unwrap();
+
+    assert_eq!(report.edges.len(), 2); // rows 1 and 3 are fine, row 3 has no timestamp
+    assert_eq!(report.edges[1].timestamp, None);
+    assert_eq!(report.skipped.len(), 2);
+    assert!(matches!(report.skipped[0], LoadError::InvalidNodeId { line: 2, .. }));
+    assert!(matches!(report.skipped[1], LoadError::MissingColumn { line: 4, .. }));
+}
+
+#[test]
+fn test_read_csv_unreadable_row() {
+    // Row 2 is not valid UTF-8
+    let path = write_temp_csv("ds210_invalid_utf8.csv", b"1,2,3,100\n\xff,2,3,100\n2,3,4,100\n");
+
+    assert!(matches!(read_csv(&path), Err(LoadError::Csv { line: 2, .. })));
+    let report = read_csv_lenient(&path).unwrap();
+    assert_eq!(report.edges.len(), 2);
+    assert!(matches!(report.skipped[0], LoadError::Csv { line: 2, .. }));
+}
+
+#[test]
+fn test_load_lenient_record_uses_delimiter() {
+    let path = write_temp_csv("ds210_lenient.tsv", "1\t2\t3\n1\tx\t3\n");
+    let report = LoaderOptions::new().delimiter(b'\t').load_lenient(&path).unwrap();
+
+    match &report.skipped[0] {
+        LoadError::InvalidNodeId { record, .. } => assert_eq!(record, "1\tx\t3"),
+        other => panic!("expected InvalidNodeId, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_loader_options_tsv_with_header_names() {
+    let path = write_temp_csv("ds210_header.tsv", "time\trater\trated\trating\n100\t1\t2\t5\n200\t2\t3\t-1\n");
+    let edges = LoaderOptions::new()
+        .delimiter(b'\t')
+        .has_headers(true)
+        .source_column("rater")
+        .target_column("rated")
+        .weight_column("rating")
+        .timestamp_column("time")
+        .load(&path)
+        .unwrap();
+
+    assert_eq!(edges.len(), 2);
+    assert_eq!((edges[1].from, edges[1].to, edges[1].weight, edges[1].timestamp), (2, 3, -1.0, Some(200)));
+}
+
+#[test]
+fn test_loader_options_comments_and_indices() {
+    let path = write_temp_csv("ds210_snap.txt", "# Directed graph\n# FromNodeId ToNodeId Sign\n1 2 1\n2 1 -1\n");
+    let edges = LoaderOptions::new().delimiter(b' ').comment(b'#').no_timestamp().load(&path).unwrap();
+
+    assert_eq!(edges.len(), 2); // comment lines are skipped
+    assert_eq!(edges[1].weight, -1.0);
+    assert_eq!(edges[1].timestamp, None);
+
+    // Unweighted edge list
+    let path = write_temp_csv("ds210_unweighted.txt", "# FromNodeId\tToNodeId\n1\t2\n2\t3\n");
+    let edges = LoaderOptions::new().delimiter(b'\t').comment(b'#').no_weight().load(&path).unwrap();
+
+    assert_eq!(edges.len(), 2);
+    assert!(edges.iter().all(|edge| edge.weight == 1.0 && edge.timestamp.is_none()));
+}
+
+#[test]
+fn test_loader_options_unknown_column() {
+    let path = write_temp_csv("ds210_unknown_column.csv", "from,to,weight\n1,2,3\n");
+    let result = LoaderOptions::new().has_headers(true).source_column("source").load(&path);
+
+    assert!(matches!(result, Err(LoadError::UnknownColumn { .. })));
+}
+
+// Write a gzip compressed edge list to the temp directory.
+#[cfg(test)]
+fn write_temp_gzip(name: &str, contents: &str) -> String {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let path = temp_path(name);
+    let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+    encoder.write_all(contents.as_bytes()).unwrap();
+    encoder.finish().unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_read_csv_gzip_extension() {
+    let path = write_temp_gzip("ds210_edges.csv.gz", "1,2,10,100\n2,3,-5,200\n");
+    let edges = read_csv(&path).unwrap();
+
+    assert_eq!(edges.len(), 2);
+    assert_eq!((edges[1].from, edges[1].to, edges[1].weight), (2, 3, -5.0));
+}
+
+#[test]
+fn test_read_csv_gzip_magic_bytes() {
+    // No .gz extension, detected from the content
+    let path = write_temp_gzip("ds210_edges_compressed.csv", "1,2,10,100\n");
+    let edges = read_csv(&path).unwrap();
 
-    println!("Test passed: Correct number of edges and nodes detected.");
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[0].timestamp, Some(100));
 }
@@ -1,45 +1,1002 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
 
 use crate::graph::Edge;
+#[cfg(test)]
 use crate::graph::Graph;
+use crate::graph::Network;
 
-// BFS algorithm that finds all connected node in a subgraph
-pub fn bfs(graph: &Graph, start_node: usize, visited: &mut HashSet<usize>) -> HashSet<usize> {
-    let mut sub_graph_nodes = HashSet::new();
+// Result of a BFS: hop distance of every reached node from the root,
+// and the node it was reached from.
+#[derive(Debug, Clone)]
+pub struct BfsTree {
+    pub root: usize,
+    pub distances: HashMap<usize, usize>,   // node -> number of edges from root
+    pub parents: HashMap<usize, usize>,     // node -> previous node on a shortest path (root has none)
+}
+
+impl BfsTree {
+    // All nodes reached by the search.
+    pub fn nodes(&self) -> HashSet<usize> {
+        self.distances.keys().copied().collect()
+    }
+
+    // Largest distance from the root, i.e. its eccentricity within the reached nodes.
+    pub fn eccentricity(&self) -> usize {
+        self.distances.values().copied().max().unwrap_or(0)
+    }
+
+    // A node at the largest distance from the root (smallest id on ties).
+    pub fn farthest(&self) -> usize {
+        let mut farthest = (self.root, 0);
+        for (&node, &distance) in &self.distances {
+            if distance > farthest.1 || (distance == farthest.1 && node < farthest.0) {
+                farthest = (node, distance);
+            }
+        }
+        farthest.0
+    }
+
+    // Nodes on the shortest path from the root to target, both included.
+    pub fn path_to(&self, target: usize) -> Option<Vec<usize>> {
+        if !self.distances.contains_key(&target) {
+            return None;
+        }
+
+        let mut path = vec![target];
+        let mut current = target;
+        while let Some(&parent) = self.parents.get(&current) {
+            path.push(parent);
+            current = parent;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+// BFS algorithm that finds all connected node in a subgraph,
+// ignoring edge direction. Nodes already in visited are not entered.
+// Returns the hop distances and BFS parents of the nodes it reached.
+pub fn bfs<G: Network>(graph: &G, start_node: usize, visited: &mut HashSet<usize>) -> BfsTree {
+    let mut distances = HashMap::new();
+    let mut parents = HashMap::new();
     let mut queue = VecDeque::new();
 
     queue.push_back(start_node);
     visited.insert(start_node);
+    distances.insert(start_node, 0);
 
     while let Some(node) = queue.pop_front() {
-        sub_graph_nodes.insert(node);
+        let next_distance = distances[&node] + 1;
+
+        for edge in graph.out_edges(node) {
+            if !visited.contains(&edge.to) {
+                visited.insert(edge.to);
+                distances.insert(edge.to, next_distance);
+                parents.insert(edge.to, node);
+                queue.push_back(edge.to);
+            }
+        }
+        // See if other node points to this edge
+        for edge in graph.in_edges(node) {
+            if !visited.contains(&edge.from) {
+                visited.insert(edge.from);
+                distances.insert(edge.from, next_distance);
+                parents.insert(edge.from, node);
+                queue.push_back(edge.from);
+            }
+        }
+    }
+
+    BfsTree { root: start_node, distances, parents }
+}
+
+// Eccentricity of a node: the largest hop distance from it to any node of its
+// sub graph (edge direction ignored).
+pub fn eccentricity<G: Network>(graph: &G, node: usize) -> usize {
+    bfs(graph, node, &mut HashSet::new()).eccentricity()
+}
+
+// Eccentricity of every node, one BFS per node. Only for small graphs.
+pub fn eccentricities<G: Network>(graph: &G) -> HashMap<usize, usize> {
+    graph.nodes().map(|node| (node, eccentricity(graph, node))).collect()
+}
+
+// Exact diameter (largest eccentricity) of a connected graph, O(n * m).
+pub fn diameter_exact<G: Network>(graph: &G) -> usize {
+    eccentricities(graph).values().copied().max().unwrap_or(0)
+}
+
+// Exact radius (smallest eccentricity) of a connected graph, O(n * m).
+pub fn radius_exact<G: Network>(graph: &G) -> usize {
+    eccentricities(graph).values().copied().min().unwrap_or(0)
+}
+
+// Double sweep estimate of the diameter of a connected graph: BFS from the
+// highest degree node, then BFS again from the farthest node found.
+// The result is a lower bound that is exact or close on real networks.
+pub fn diameter_double_sweep<G: Network>(graph: &G) -> usize {
+    let start = graph.nodes()
+        .max_by_key(|&node| (graph.out_degree(node) + graph.in_degree(node), std::cmp::Reverse(node)));
+
+    match start {
+        Some(start) => {
+            let first_sweep = bfs(graph, start, &mut HashSet::new());
+            eccentricity(graph, first_sweep.farthest())
+        }
+        None => 0,
+    }
+}
+
+// Graphs up to this many nodes get an exact diameter.
+pub const EXACT_DIAMETER_LIMIT: usize = 1000;
 
-        if let Some(edges) = graph.content.get(&node) {
-            for edge in edges {
+// Diameter of a connected graph and whether it is exact or a double sweep estimate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Diameter {
+    pub value: usize,
+    pub exact: bool,
+}
+
+// Exact diameter for small graphs, double sweep estimate for large ones.
+pub fn diameter<G: Network>(graph: &G) -> Diameter {
+    if graph.node_count() <= EXACT_DIAMETER_LIMIT {
+        Diameter { value: diameter_exact(graph), exact: true }
+    } else {
+        Diameter { value: diameter_double_sweep(graph), exact: false }
+    }
+}
+
+// Default cost of following a rating for the shortest path functions.
+// Ratings are in [-10, 10]; only positive ratings can be followed and a
+// rating of 10 costs 1 while a rating of 1 costs 10, so "close" means
+// connected through strong trust. Weights above 10 are outside the scale
+// and can't be followed either, since they would give a negative cost.
+pub fn trust_distance(edge: &Edge) -> Option<f64> {
+    if edge.weight > 0.0 && edge.weight <= 10.0 {
+        Some(11.0 - edge.weight)
+    } else {
+        None
+    }
+}
+
+// Entry of the Dijkstra priority queue, ordered so the smallest cost pops first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct QueueEntry {
+    cost: f64,
+    node: usize,
+}
+
+impl Eq for QueueEntry {}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost).then_with(|| other.node.cmp(&self.node))
+    }
+}
 
-                if !visited.contains(&edge.to) {
-                    visited.insert(edge.to);
-                    queue.push_back(edge.to);
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Result of a single source shortest path search.
+#[derive(Debug, Clone)]
+pub struct ShortestPaths {
+    pub source: usize,
+    pub distances: HashMap<usize, f64>,     // node -> cost of the cheapest path from source
+    pub previous: HashMap<usize, usize>,    // node -> node before it on that path
+}
+
+impl ShortestPaths {
+    // Cost of the cheapest path to target, None if it can't be reached.
+    pub fn distance_to(&self, target: usize) -> Option<f64> {
+        self.distances.get(&target).copied()
+    }
+
+    // Nodes of the cheapest path from source to target, both included.
+    pub fn path_to(&self, target: usize) -> Option<Vec<usize>> {
+        if !self.distances.contains_key(&target) {
+            return None;
+        }
+
+        let mut path = vec![target];
+        let mut current = target;
+        while let Some(&previous) = self.previous.get(&current) {
+            path.push(previous);
+            current = previous;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+// Dijkstra from source, using trust_distance as the edge cost.
+pub fn dijkstra<G: Network>(graph: &G, source: usize) -> ShortestPaths {
+    dijkstra_with(graph, source, trust_distance)
+}
+
+// Dijkstra from source with a custom edge cost. Edges where cost returns None
+// are not followed. Costs must not be negative.
+pub fn dijkstra_with<G: Network, F: Fn(&Edge) -> Option<f64>>(graph: &G, source: usize, cost: F) -> ShortestPaths {
+    dijkstra_search(graph, source, None, &cost)
+}
+
+// Cheapest path from source to target through positive ratings,
+// as (total cost, nodes of the path). None if there is no such path.
+pub fn shortest_path<G: Network>(graph: &G, source: usize, target: usize) -> Option<(f64, Vec<usize>)> {
+    let paths = dijkstra_search(graph, source, Some(target), &trust_distance);
+    Some((paths.distance_to(target)?, paths.path_to(target)?))
+}
+
+// Shared Dijkstra loop. Stops early once target is settled.
+fn dijkstra_search<G: Network, F: Fn(&Edge) -> Option<f64>>(graph: &G, source: usize, target: Option<usize>, cost: &F) -> ShortestPaths {
+    let mut distances: HashMap<usize, f64> = HashMap::new();
+    let mut previous: HashMap<usize, usize> = HashMap::new();
+    let mut settled: HashSet<usize> = HashSet::new();
+    let mut queue = BinaryHeap::new();
+
+    if graph.contains(source) {
+        distances.insert(source, 0.0);
+        queue.push(QueueEntry { cost: 0.0, node: source });
+    }
+
+    while let Some(QueueEntry { cost: node_cost, node }) = queue.pop() {
+        if !settled.insert(node) {
+            continue; // Stale entry, node was already reached more cheaply
+        }
+        if Some(node) == target {
+            break;
+        }
+
+        for edge in graph.out_edges(node) {
+            if let Some(edge_cost) = cost(&edge) {
+                let next_cost = node_cost + edge_cost;
+                let improves = match distances.get(&edge.to) {
+                    Some(&known) => next_cost < known,
+                    None => true,
+                };
+                if improves && !settled.contains(&edge.to) {
+                    distances.insert(edge.to, next_cost);
+                    previous.insert(edge.to, node);
+                    queue.push(QueueEntry { cost: next_cost, node: edge.to });
                 }
-                if !visited.contains(&edge.from) {
-                    visited.insert(edge.from);
-                    queue.push_back(edge.from);
+            }
+        }
+    }
+
+    ShortestPaths { source, distances, previous }
+}
+
+// Direction to follow edges in for the directed distance based measures.
+// Out measures how well a node reaches others through its ratings,
+// In measures how well others reach the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    In,
+    Out,
+}
+
+// Per source BFS totals used by closeness and harmonic centrality:
+// (number of other nodes reached, sum of their distances, sum of 1 / distance)
+fn distance_totals<G: Network>(graph: &G, direction: Direction) -> HashMap<usize, (usize, usize, f64)> {
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort();
+    let index_of: HashMap<usize, usize> = nodes.iter().enumerate().map(|(index, &node)| (node, index)).collect();
+
+    let adjacency: Vec<Vec<usize>> = nodes.iter()
+        .map(|&node| match direction {
+            Direction::Out => graph.out_edges(node).map(|edge| index_of[&edge.to]).collect(),
+            Direction::In => graph.in_edges(node).map(|edge| index_of[&edge.from]).collect(),
+        })
+        .collect();
+
+    let mut distance = vec![usize::MAX; nodes.len()];
+    let mut totals = HashMap::new();
+    for source in 0..nodes.len() {
+        distance.iter_mut().for_each(|value| *value = usize::MAX);
+        distance[source] = 0;
+
+        let (mut reached, mut distance_sum, mut harmonic_sum) = (0, 0, 0.0);
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            for &next in &adjacency[node] {
+                if distance[next] == usize::MAX {
+                    distance[next] = distance[node] + 1;
+                    reached += 1;
+                    distance_sum += distance[next];
+                    harmonic_sum += 1.0 / distance[next] as f64;
+                    queue.push_back(next);
                 }
             }
         }
-        // See if other node points to this edge
-        for (other, edges) in &graph.content.clone() {
-            for edge in edges {
-                if edge.to == node && !visited.contains(&other) {
-                    visited.insert(*other);
-                    queue.push_back(*other);
+        totals.insert(nodes[source], (reached, distance_sum, harmonic_sum));
+    }
+    totals
+}
+
+// Closeness centrality of every node in the given direction.
+// Uses the Wasserman-Faust form (r / (n - 1)) * (r / sum of distances),
+// where r is the number of nodes reached, so nodes in small sub graphs
+// don't get a perfect score. Nodes that reach nobody get 0.
+pub fn closeness_centrality<G: Network>(graph: &G, direction: Direction) -> HashMap<usize, f64> {
+    let num_nodes = graph.node_count();
+
+    distance_totals(graph, direction).into_iter()
+        .map(|(node, (reached, distance_sum, _))| {
+            let closeness = if distance_sum == 0 {
+                0.0
+            } else {
+                (reached as f64 / (num_nodes - 1) as f64) * (reached as f64 / distance_sum as f64)
+            };
+            (node, closeness)
+        })
+        .collect()
+}
+
+// Harmonic centrality of every node in the given direction:
+// the sum of 1 / distance over all other nodes, unreachable nodes adding 0.
+// Works on disconnected graphs without any correction.
+pub fn harmonic_centrality<G: Network>(graph: &G, direction: Direction) -> HashMap<usize, f64> {
+    distance_totals(graph, direction).into_iter()
+        .map(|(node, (_, _, harmonic_sum))| (node, harmonic_sum))
+        .collect()
+}
+
+// Options for betweenness_centrality.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BetweennessOptions {
+    pub weighted: bool,             // Use trust_distance as edge length (positive ratings only)
+    pub samples: Option<usize>,     // Only use this many random source nodes (approximate)
+    pub seed: u64,                  // Seed for picking the sample
+}
+
+impl Default for BetweennessOptions {
+    fn default() -> BetweennessOptions {
+        BetweennessOptions { weighted: false, samples: None, seed: 42 }
+    }
+}
+
+// Betweenness centrality of every node using Brandes' algorithm on the directed graph.
+// The score of v is the number of shortest paths between other pairs of nodes
+// that go through v (not normalized). Unweighted paths count hops over every
+// rating; weighted paths use trust_distance, so only positive ratings are followed.
+// With options.samples = Some(k), only k source nodes are used and the
+// result is scaled by n / k, which is much faster on large graphs.
+pub fn betweenness_centrality<G: Network>(graph: &G, options: &BetweennessOptions) -> HashMap<usize, f64> {
+    // Dense indices and adjacency lists for speed
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort();
+    let index_of: HashMap<usize, usize> = nodes.iter().enumerate().map(|(index, &node)| (node, index)).collect();
+    let num_nodes = nodes.len();
+
+    let adjacency: Vec<Vec<(usize, f64)>> = nodes.iter()
+        .map(|&node| {
+            graph.out_edges(node)
+                .filter_map(|edge| {
+                    let length = if options.weighted { trust_distance(&edge)? } else { 1.0 };
+                    Some((index_of[&edge.to], length))
+                })
+                .collect()
+        })
+        .collect();
+
+    let sources: Vec<usize> = match options.samples {
+        Some(samples) if samples < num_nodes => sample_indices(num_nodes, samples, options.seed),
+        _ => (0..num_nodes).collect(),
+    };
+
+    let mut centrality = vec![0.0; num_nodes];
+    let mut sigma = vec![0.0; num_nodes];           // Number of shortest paths from source
+    let mut distance = vec![f64::INFINITY; num_nodes];
+    let mut delta = vec![0.0; num_nodes];           // Dependency of source on each node
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); num_nodes];
+
+    for &source in &sources {
+        for index in 0..num_nodes {
+            sigma[index] = 0.0;
+            distance[index] = f64::INFINITY;
+            delta[index] = 0.0;
+            predecessors[index].clear();
+        }
+        sigma[source] = 1.0;
+        distance[source] = 0.0;
+
+        // Nodes in order of non-decreasing distance from source
+        let order = if options.weighted {
+            brandes_dijkstra(&adjacency, source, &mut sigma, &mut distance, &mut predecessors)
+        } else {
+            brandes_bfs(&adjacency, source, &mut sigma, &mut distance, &mut predecessors)
+        };
+
+        // Accumulate dependencies from the farthest nodes back
+        for &node in order.iter().rev() {
+            for &predecessor in &predecessors[node] {
+                delta[predecessor] += sigma[predecessor] / sigma[node] * (1.0 + delta[node]);
+            }
+            if node != source {
+                centrality[node] += delta[node];
+            }
+        }
+    }
+
+    let scale = if sources.is_empty() { 0.0 } else { num_nodes as f64 / sources.len() as f64 };
+    nodes.iter().enumerate().map(|(index, &node)| (node, centrality[index] * scale)).collect()
+}
+
+// Unweighted single source step of Brandes' algorithm.
+fn brandes_bfs(adjacency: &[Vec<(usize, f64)>], source: usize, sigma: &mut [f64],
+               distance: &mut [f64], predecessors: &mut [Vec<usize>]) -> Vec<usize> {
+    let mut order = Vec::new();
+    let mut queue = VecDeque::from([source]);
+
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        for &(next, _) in &adjacency[node] {
+            if distance[next].is_infinite() {
+                distance[next] = distance[node] + 1.0;
+                queue.push_back(next);
+            }
+            if distance[next] == distance[node] + 1.0 {
+                sigma[next] += sigma[node];
+                predecessors[next].push(node);
+            }
+        }
+    }
+    order
+}
+
+// Weighted single source step of Brandes' algorithm.
+fn brandes_dijkstra(adjacency: &[Vec<(usize, f64)>], source: usize, sigma: &mut [f64],
+                    distance: &mut [f64], predecessors: &mut [Vec<usize>]) -> Vec<usize> {
+    let mut order = Vec::new();
+    let mut settled = vec![false; adjacency.len()];
+    let mut queue = BinaryHeap::from([QueueEntry { cost: 0.0, node: source }]);
+
+    while let Some(QueueEntry { cost, node }) = queue.pop() {
+        if settled[node] {
+            continue;
+        }
+        settled[node] = true;
+        order.push(node);
+
+        for &(next, length) in &adjacency[node] {
+            let next_cost = cost + length;
+            if next_cost < distance[next] {
+                distance[next] = next_cost;
+                sigma[next] = sigma[node];
+                predecessors[next].clear();
+                predecessors[next].push(node);
+                queue.push(QueueEntry { cost: next_cost, node: next });
+            } else if next_cost == distance[next] && !settled[next] {
+                sigma[next] += sigma[node];
+                predecessors[next].push(node);
+            }
+        }
+    }
+    order
+}
+
+// Pick `count` distinct indices out of 0..total with a seeded xorshift generator,
+// so approximate results can be reproduced.
+fn sample_indices(total: usize, count: usize, seed: u64) -> Vec<usize> {
+    let mut state = seed.max(1);
+    let mut indices: Vec<usize> = (0..total).collect();
+
+    // Partial Fisher-Yates shuffle
+    for i in 0..count.min(total) {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let j = i + (state % (total - i) as u64) as usize;
+        indices.swap(i, j);
+    }
+    indices.truncate(count.min(total));
+    indices
+}
+
+// Options for hits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HitsOptions {
+    pub positive_only: bool,        // Only follow positive ratings
+    pub tolerance: f64,             // Stop when the L1 change of the scores is below this
+    pub max_iterations: usize,
+}
+
+impl Default for HitsOptions {
+    fn default() -> HitsOptions {
+        HitsOptions { positive_only: false, tolerance: 1e-10, max_iterations: 100 }
+    }
+}
+
+// Result of hits, both scores sum to 1.
+#[derive(Debug, Clone)]
+pub struct Hits {
+    pub hubs: HashMap<usize, f64>,          // Rates many good authorities, i.e. good raters
+    pub authorities: HashMap<usize, f64>,   // Rated by many good hubs, i.e. well rated users
+}
+
+// Kleinberg's HITS on the directed graph, every rating counts as a link
+// regardless of its weight:
+//   authority(v) = sum of hub(u) over ratings u -> v
+//   hub(u) = sum of authority(v) over ratings u -> v
+pub fn hits<G: Network>(graph: &G, options: &HitsOptions) -> Hits {
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort();
+    let index_of: HashMap<usize, usize> = nodes.iter().enumerate().map(|(index, &node)| (node, index)).collect();
+    let num_nodes = nodes.len();
+
+    let links: Vec<(usize, usize)> = nodes.iter()
+        .flat_map(|&node| graph.out_edges(node))
+        .filter(|edge| !options.positive_only || edge.weight > 0.0)
+        .map(|edge| (index_of[&edge.from], index_of[&edge.to]))
+        .collect();
+
+    let mut hubs = vec![1.0 / num_nodes as f64; num_nodes];
+    let mut authorities = vec![0.0; num_nodes];
+    for _ in 0..options.max_iterations {
+        let mut next_authorities = vec![0.0; num_nodes];
+        for &(from, to) in &links {
+            next_authorities[to] += hubs[from];
+        }
+        normalize_sum(&mut next_authorities);
+
+        let mut next_hubs = vec![0.0; num_nodes];
+        for &(from, to) in &links {
+            next_hubs[from] += next_authorities[to];
+        }
+        normalize_sum(&mut next_hubs);
+
+        let change: f64 = hubs.iter().zip(next_hubs.iter()).map(|(old, new)| (old - new).abs()).sum::<f64>()
+            + authorities.iter().zip(next_authorities.iter()).map(|(old, new)| (old - new).abs()).sum::<f64>();
+        hubs = next_hubs;
+        authorities = next_authorities;
+        if change < options.tolerance {
+            break;
+        }
+    }
+
+    Hits {
+        hubs: nodes.iter().copied().zip(hubs).collect(),
+        authorities: nodes.into_iter().zip(authorities).collect(),
+    }
+}
+
+// helper that scales values to sum to 1 (left as is if they are all 0)
+fn normalize_sum(values: &mut [f64]) {
+    let total: f64 = values.iter().sum();
+    if total > 0.0 {
+        values.iter_mut().for_each(|value| *value /= total);
+    }
+}
+
+// Triangles of the undirected projection of the graph (two nodes are linked
+// if either rated the other) and the clustering measures built on them.
+#[derive(Debug, Clone)]
+pub struct TriangleStats {
+    pub triangles: HashMap<usize, usize>,           // Triangles each node is part of
+    pub total_triangles: usize,
+    pub transitivity: f64,                          // 3 * triangles / connected triples
+    pub local_clustering: HashMap<usize, f64>,      // Undirected, 0 for nodes with less than 2 neighbours
+    pub rating_clustering: HashMap<usize, f64>,     // Ratings between neighbours over possible ones, see Network::clustering_coefficient
+    pub directed_clustering: HashMap<usize, f64>,   // Fagiolo's directed clustering
+    pub average_clustering: f64,
+    pub average_directed_clustering: f64,
+}
+
+// Count every triangle once by orienting each link from the lower to the
+// higher degree node and merging the sorted forward neighbour lists, which
+// takes O(m^1.5) instead of going through every pair of neighbours.
+// The ratings along the side of a triangle opposite a node are the ratings
+// between its neighbours, which gives Network::clustering_coefficient too.
+//
+// Fagiolo's (2007) directed clustering of node i counts the directed triangles
+// around i, i.e. (A + A^T)^3_ii / 2, over the most there could be,
+// d_tot(i) * (d_tot(i) - 1) - 2 * d_both(i), where d_tot is in + out degree and
+// d_both the number of neighbours rated in both directions. Self ratings and
+// repeated ratings are ignored.
+pub fn triangle_stats<G: Network>(graph: &G) -> TriangleStats {
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort();
+    let index_of: HashMap<usize, usize> = nodes.iter().enumerate().map(|(index, &node)| (node, index)).collect();
+    let num_nodes = nodes.len();
+
+    // Links of the projection, 2 if the nodes rated each other and 1 otherwise
+    let mut links: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut directed_degree = vec![0usize; num_nodes];
+    for (from, &node) in nodes.iter().enumerate() {
+        let targets: HashSet<usize> = graph.out_edges(node)
+            .map(|edge| index_of[&edge.to])
+            .filter(|&to| to != from)
+            .collect();
+        for to in targets {
+            directed_degree[from] += 1;
+            directed_degree[to] += 1;
+            *links.entry((from.min(to), from.max(to))).or_insert(0) += 1;
+        }
+    }
+
+    let mut degree = vec![0usize; num_nodes];
+    let mut reciprocal = vec![0usize; num_nodes];
+    for (&(a, b), &multiplicity) in &links {
+        degree[a] += 1;
+        degree[b] += 1;
+        if multiplicity == 2 {
+            reciprocal[a] += 1;
+            reciprocal[b] += 1;
+        }
+    }
+
+    // Orient links by (degree, index) and sort the forward lists by that order
+    let mut by_degree: Vec<usize> = (0..num_nodes).collect();
+    by_degree.sort_by_key(|&node| (degree[node], node));
+    let mut rank = vec![0; num_nodes];
+    for (position, &node) in by_degree.iter().enumerate() {
+        rank[node] = position;
+    }
+    let mut forward: Vec<Vec<(usize, usize, usize)>> = vec![Vec::new(); num_nodes];     // (rank, node, multiplicity)
+    for (&(a, b), &multiplicity) in &links {
+        let (low, high) = if rank[a] < rank[b] { (a, b) } else { (b, a) };
+        forward[low].push((rank[high], high, multiplicity));
+    }
+    for neighbors in forward.iter_mut() {
+        neighbors.sort();
+    }
+
+    let mut triangles = vec![0usize; num_nodes];
+    let mut directed_triangles = vec![0usize; num_nodes];
+    let mut neighbor_ratings = vec![0usize; num_nodes];     // Ratings between the node's neighbours
+    for u in 0..num_nodes {
+        for &(_, v, uv) in &forward[u] {
+            // Common forward neighbours of u and v
+            let (mut i, mut j) = (0, 0);
+            while i < forward[u].len() && j < forward[v].len() {
+                let (rank_u, w, uw) = forward[u][i];
+                let (rank_v, _, vw) = forward[v][j];
+                match rank_u.cmp(&rank_v) {
+                    Ordering::Less => i += 1,
+                    Ordering::Greater => j += 1,
+                    Ordering::Equal => {
+                        for (node, opposite) in [(u, vw), (v, uw), (w, uv)] {
+                            triangles[node] += 1;
+                            directed_triangles[node] += uv * uw * vw;
+                            neighbor_ratings[node] += opposite;
+                        }
+                        i += 1;
+                        j += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut local_clustering = HashMap::new();
+    let mut rating_clustering = HashMap::new();
+    let mut directed_clustering = HashMap::new();
+    let mut triples = 0;
+    for (index, &node) in nodes.iter().enumerate() {
+        let pairs = degree[index] * degree[index].saturating_sub(1) / 2;
+        triples += pairs;
+        let local = if pairs == 0 { 0.0 } else { triangles[index] as f64 / pairs as f64 };
+        local_clustering.insert(node, local);
+        let rating = if pairs == 0 { 0.0 } else { neighbor_ratings[index] as f64 / (2 * pairs) as f64 };
+        rating_clustering.insert(node, rating);
+
+        let possible = (directed_degree[index] * directed_degree[index].saturating_sub(1)).saturating_sub(2 * reciprocal[index]);
+        let directed = if possible == 0 { 0.0 } else { directed_triangles[index] as f64 / possible as f64 };
+        directed_clustering.insert(node, directed);
+    }
+
+    let total_triangles = triangles.iter().sum::<usize>() / 3;
+    let average = |values: &HashMap<usize, f64>| if num_nodes == 0 { 0.0 } else { values.values().sum::<f64>() / num_nodes as f64 };
+
+    TriangleStats {
+        triangles: nodes.iter().copied().zip(triangles).collect(),
+        total_triangles,
+        transitivity: if triples == 0 { 0.0 } else { 3.0 * total_triangles as f64 / triples as f64 },
+        average_clustering: average(&local_clustering),
+        average_directed_clustering: average(&directed_clustering),
+        local_clustering,
+        rating_clustering,
+        directed_clustering,
+    }
+}
+
+// Which degree a core decomposition peels by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DegreeKind {
+    In,         // Ratings received
+    Out,        // Ratings given
+    Total,      // Both
+}
+
+// k-core decomposition: the core number of a node is the largest k such that
+// it belongs to a subgraph where every node has at least k ratings of the given
+// kind inside the subgraph. Self ratings are ignored.
+pub fn core_numbers<G: Network>(graph: &G, kind: DegreeKind) -> HashMap<usize, usize> {
+    peel(graph, kind, |_| 1.0).into_iter()
+        .map(|(node, core)| (node, core as usize))
+        .collect()
+}
+
+// s-core decomposition, same as core_numbers but a node's degree is the sum of
+// the absolute weights of its ratings (its strength), so a few strong ratings
+// count as much as many weak ones.
+pub fn s_core_numbers<G: Network>(graph: &G, kind: DegreeKind) -> HashMap<usize, f64> {
+    peel(graph, kind, |edge| edge.weight.abs())
+}
+
+// Generalized core decomposition (Batagelj and Zaversnik): repeatedly remove
+// the node with the smallest remaining degree, where every edge adds
+// `contribution` to the degree. The core value of a node is the largest
+// degree removed up to and including it.
+fn peel<G: Network, F: Fn(&Edge) -> f64>(graph: &G, kind: DegreeKind, contribution: F) -> HashMap<usize, f64> {
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort();
+    let index_of: HashMap<usize, usize> = nodes.iter().enumerate().map(|(index, &node)| (node, index)).collect();
+    let num_nodes = nodes.len();
+
+    // degree[v], and the nodes whose degree drops when v is removed
+    let mut degree = vec![0.0; num_nodes];
+    let mut dependents: Vec<Vec<(usize, f64)>> = vec![Vec::new(); num_nodes];
+    for edge in nodes.iter().flat_map(|&node| graph.out_edges(node)) {
+        if edge.from == edge.to {
+            continue;
+        }
+        let (from, to, value) = (index_of[&edge.from], index_of[&edge.to], contribution(&edge));
+        if kind != DegreeKind::Out {
+            degree[to] += value;
+            dependents[from].push((to, value));
+        }
+        if kind != DegreeKind::In {
+            degree[from] += value;
+            dependents[to].push((from, value));
+        }
+    }
+
+    let mut queue: BinaryHeap<QueueEntry> = (0..num_nodes).map(|node| QueueEntry { cost: degree[node], node }).collect();
+    let mut removed = vec![false; num_nodes];
+    let mut cores = vec![0.0; num_nodes];
+    let mut current_core: f64 = 0.0;
+
+    while let Some(QueueEntry { cost, node }) = queue.pop() {
+        // Skip nodes already removed and outdated entries
+        if removed[node] || cost != degree[node] {
+            continue;
+        }
+        removed[node] = true;
+        current_core = current_core.max(cost);
+        cores[node] = current_core;
+
+        for &(next, value) in &dependents[node] {
+            if !removed[next] {
+                degree[next] -= value;
+                queue.push(QueueEntry { cost: degree[next], node: next });
+            }
+        }
+    }
+
+    nodes.into_iter().zip(cores).collect()
+}
+
+// Disjoint-set forest (union-find) with path compression and union by size.
+// Elements are dense indices 0..n.
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    pub fn new(num_elements: usize) -> UnionFind {
+        UnionFind { parent: (0..num_elements).collect(), size: vec![1; num_elements] }
+    }
+
+    // Representative of the set containing element.
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // Path compression: point everything on the way directly to the root
+        let mut current = element;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    // Merge the sets of a and b. Returns false if they were already together.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut root_a, mut root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return false;
+        }
+        if self.size[root_a] < self.size[root_b] {
+            std::mem::swap(&mut root_a, &mut root_b);
+        }
+        self.parent[root_b] = root_a;
+        self.size[root_a] += self.size[root_b];
+        true
+    }
+}
+
+// Weakly connected components of a graph (edge direction ignored).
+// Components are numbered by their smallest node id, so the numbering
+// is the same on every run.
+#[derive(Debug, Clone)]
+pub struct WeakComponents {
+    component_of: HashMap<usize, usize>,    // node -> component index
+    members: Vec<Vec<usize>>,               // component index -> sorted nodes
+}
+
+impl WeakComponents {
+    // Index of the component containing node, None if the node isn't in the graph.
+    pub fn component_of(&self, node: usize) -> Option<usize> {
+        self.component_of.get(&node).copied()
+    }
+
+    // Nodes of a component, sorted by id.
+    pub fn members(&self, component: usize) -> &[usize] {
+        &self.members[component]
+    }
+
+    // Number of nodes in each component.
+    pub fn sizes(&self) -> Vec<usize> {
+        self.members.iter().map(|members| members.len()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+// Weakly connected components using union-find, in a single pass over the edges.
+pub fn weak_components<G: Network>(graph: &G) -> WeakComponents {
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort();
+    let dense: HashMap<usize, usize> = nodes.iter().enumerate().map(|(index, &node)| (node, index)).collect();
+
+    let mut union_find = UnionFind::new(nodes.len());
+    for edge in graph.edges() {
+        if let (Some(&from), Some(&to)) = (dense.get(&edge.from), dense.get(&edge.to)) {
+            union_find.union(from, to);
+        }
+    }
+
+    // Number the components in node order
+    let mut component_of = HashMap::new();
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    let mut component_of_root: HashMap<usize, usize> = HashMap::new();
+    for (index, &node) in nodes.iter().enumerate() {
+        let root = union_find.find(index);
+        let component = *component_of_root.entry(root).or_insert_with(|| {
+            members.push(Vec::new());
+            members.len() - 1
+        });
+        component_of.insert(node, component);
+        members[component].push(node);
+    }
+
+    WeakComponents { component_of, members }
+}
+
+// Strongly connected components using Tarjan's algorithm.
+// Unlike bfs this follows edge direction: two nodes are in the same
+// component only if each can reach the other through directed ratings.
+// Written with an explicit stack so long chains don't overflow the call stack.
+// Components come out in reverse topological order of the condensation.
+pub fn strongly_connected_components<G: Network>(graph: &G) -> Vec<HashSet<usize>> {
+    let mut index_of: HashMap<usize, usize> = HashMap::new();
+    let mut lowlink: HashMap<usize, usize> = HashMap::new();
+    let mut on_stack: HashSet<usize> = HashSet::new();
+    let mut stack: Vec<usize> = Vec::new();
+    let mut components: Vec<HashSet<usize>> = Vec::new();
+    let mut next_index = 0;
+
+    // Visit nodes in id order so the result is the same on every run
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort();
+
+    for &start in &nodes {
+        if index_of.contains_key(&start) {
+            continue;
+        }
+
+        // (node, out edges still to look at)
+        let mut call_stack = vec![(start, graph.out_edges(start))];
+        index_of.insert(start, next_index);
+        lowlink.insert(start, next_index);
+        next_index += 1;
+        stack.push(start);
+        on_stack.insert(start);
+
+        while let Some((node, edges)) = call_stack.last_mut() {
+            let node = *node;
+
+            if let Some(edge) = edges.next() {
+                let next_node = edge.to;
+
+                if let Some(&next_node_index) = index_of.get(&next_node) {
+                    if on_stack.contains(&next_node) {
+                        let low = lowlink[&node].min(next_node_index);
+                        lowlink.insert(node, low);
+                    }
+                } else {
+                    index_of.insert(next_node, next_index);
+                    lowlink.insert(next_node, next_index);
+                    next_index += 1;
+                    stack.push(next_node);
+                    on_stack.insert(next_node);
+                    call_stack.push((next_node, graph.out_edges(next_node)));
+                }
+            } else {
+                call_stack.pop();
+                if let Some((parent, _)) = call_stack.last() {
+                    let low = lowlink[parent].min(lowlink[&node]);
+                    lowlink.insert(*parent, low);
+                }
+
+                // node is the root of a component, pop it off the stack
+                if lowlink[&node] == index_of[&node] {
+                    let mut component = HashSet::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack.remove(&member);
+                        component.insert(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
                 }
             }
         }
     }
 
-    return sub_graph_nodes;
+    components
+}
+
+// Condensation of a graph: every strongly connected component becomes
+// a single node, and there is an edge between two components if any
+// of their nodes are linked. The result is always a DAG.
+#[derive(Debug, Clone)]
+pub struct Condensation {
+    pub component_of: HashMap<usize, usize>,        // node -> index of its component
+    pub dag: HashMap<usize, HashSet<usize>>,        // component -> components it points to
+}
+
+// Build the condensation DAG from the output of strongly_connected_components.
+// Edges to or from nodes that aren't in any component are skipped.
+pub fn condensation<G: Network>(graph: &G, components: &[HashSet<usize>]) -> Condensation {
+    let mut component_of = HashMap::new();
+    for (component_index, component) in components.iter().enumerate() {
+        for &node in component {
+            component_of.insert(node, component_index);
+        }
+    }
+
+    let mut dag: HashMap<usize, HashSet<usize>> = (0..components.len()).map(|index| (index, HashSet::new())).collect();
+    for edge in graph.edges() {
+        let (Some(&from), Some(&to)) = (component_of.get(&edge.from), component_of.get(&edge.to)) else {
+            continue;
+        };
+        if from != to {
+            dag.entry(from).or_default().insert(to);
+        }
+    }
+
+    Condensation { component_of, dag }
 }
 
 
@@ -48,14 +1005,14 @@ pub fn bfs(graph: &Graph, start_node: usize, visited: &mut HashSet<usize>) -> Ha
 #[test]
 fn test_bfs_single_connected_component() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
-        Edge { from: 2, to: 3, weight: 1.0 },
-        Edge { from: 3, to: 4, weight: 1.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 1.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
     let mut visited = HashSet::new();
 
-    let sub_graph = bfs(&graph, 1, &mut visited);
+    let sub_graph = bfs(&graph, 1, &mut visited).nodes();
     let mut expected: HashSet<usize> = HashSet::new();
     expected.insert(1); expected.insert(2); 
     expected.insert(3); expected.insert(4);
@@ -66,14 +1023,14 @@ fn test_bfs_single_connected_component() {
 #[test]
 fn test_bfs_disconnected_graph() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
-        Edge { from: 2, to: 3, weight: 1.0 },
-        Edge { from: 4, to: 5, weight: 1.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 4, to: 5, weight: 1.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
     let mut visited = HashSet::new();
 
-    let sub_graph = bfs(&graph, 1, &mut visited);
+    let sub_graph = bfs(&graph, 1, &mut visited).nodes();
     let mut expected: HashSet<usize> = HashSet::new();
     expected.insert(1); expected.insert(2); expected.insert(3);
 
@@ -81,3 +1038,414 @@ fn test_bfs_disconnected_graph() {
     assert!(!sub_graph.contains(&4)); // Sub_graph should not contain 4
     assert!(!sub_graph.contains(&5)); // Sub_graph should not contain 5
 }
+
+#[cfg(test)]
+fn scc_test_graph() -> Graph {
+    // 1 -> 2 -> 3 -> 1 is a cycle, 3 -> 4 -> 5 -> 4 is a second cycle, 6 only points in.
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 1.0, timestamp: None },
+        Edge { from: 4, to: 5, weight: 1.0, timestamp: None },
+        Edge { from: 5, to: 4, weight: 1.0, timestamp: None },
+        Edge { from: 6, to: 5, weight: 1.0, timestamp: None },
+    ];
+    Graph::new(&edges)
+}
+
+#[test]
+fn test_strongly_connected_components() {
+    let components = strongly_connected_components(&scc_test_graph());
+
+    let mut sizes: Vec<usize> = components.iter().map(|component| component.len()).collect();
+    sizes.sort();
+    assert_eq!(sizes, vec![1, 2, 3]);
+
+    let cycle: HashSet<usize> = [1, 2, 3].into_iter().collect();
+    assert!(components.contains(&cycle));
+}
+
+#[test]
+fn test_strongly_connected_components_no_cycles() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+    ];
+    let components = strongly_connected_components(&Graph::new(&edges));
+
+    assert_eq!(components.len(), 3); // Every node is its own component
+}
+
+#[test]
+fn test_condensation() {
+    let graph = scc_test_graph();
+    let components = strongly_connected_components(&graph);
+    let condensed = condensation(&graph, &components);
+
+    let (cycle, pair, single) = (condensed.component_of[&1], condensed.component_of[&4], condensed.component_of[&6]);
+    assert_eq!(condensed.component_of[&2], cycle);
+    assert_eq!(condensed.component_of[&5], pair);
+    assert!(condensed.dag[&cycle].contains(&pair));
+    assert!(condensed.dag[&single].contains(&pair));
+    assert!(condensed.dag[&pair].is_empty()); // Sink of the DAG
+}
+
+#[test]
+fn test_condensation_edges_outside_components() {
+    // Components only cover 1 and 2, the edge 2 -> 3 points outside them
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+    ];
+    let components: Vec<HashSet<usize>> = vec![HashSet::from([1]), HashSet::from([2])];
+    let condensed = condensation(&Graph::new(&edges), &components);
+
+    assert!(!condensed.component_of.contains_key(&3));
+    assert!(condensed.dag[&0].contains(&1));
+    assert!(condensed.dag[&1].is_empty());
+}
+
+#[test]
+fn test_union_find() {
+    let mut union_find = UnionFind::new(5);
+
+    assert!(union_find.union(0, 1));
+    assert!(union_find.union(3, 4));
+    assert!(!union_find.union(1, 0)); // Already in the same set
+    assert_eq!(union_find.find(0), union_find.find(1));
+    assert_ne!(union_find.find(1), union_find.find(3));
+    assert_eq!(union_find.find(2), 2); // Untouched element is its own set
+}
+
+#[test]
+fn test_weak_components() {
+    let edges = vec![
+        Edge { from: 5, to: 4, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 1, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 2, weight: 1.0, timestamp: None },
+    ];
+    let components = weak_components(&Graph::new(&edges));
+
+    assert_eq!(components.len(), 2);
+    assert_eq!(components.component_of(1), Some(0)); // Numbered by smallest node id
+    assert_eq!(components.component_of(3), Some(0));
+    assert_eq!(components.component_of(5), Some(1));
+    assert_eq!(components.component_of(9), None);
+    assert_eq!(components.members(1), &[4, 5]);
+    assert_eq!(components.sizes(), vec![3, 2]);
+}
+
+#[cfg(test)]
+fn path_test_graph() -> Graph {
+    // 1 -> 2 -> 4 costs 1 + 1, 1 -> 3 -> 4 costs 6 + 6, 1 -> 4 directly is a negative rating.
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 10.0, timestamp: None },
+        Edge { from: 2, to: 4, weight: 10.0, timestamp: None },
+        Edge { from: 1, to: 3, weight: 5.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 5.0, timestamp: None },
+        Edge { from: 1, to: 4, weight: -10.0, timestamp: None },
+        Edge { from: 4, to: 5, weight: -1.0, timestamp: None },
+    ];
+    Graph::new(&edges)
+}
+
+#[test]
+fn test_dijkstra() {
+    let paths = dijkstra(&path_test_graph(), 1);
+
+    assert_eq!(paths.distance_to(1), Some(0.0));
+    assert_eq!(paths.distance_to(3), Some(6.0));
+    assert_eq!(paths.distance_to(4), Some(2.0));
+    assert_eq!(paths.path_to(4), Some(vec![1, 2, 4]));
+    assert_eq!(paths.distance_to(5), None); // Only reachable through a negative rating
+    assert_eq!(paths.path_to(5), None);
+}
+
+#[test]
+fn test_shortest_path() {
+    let graph = path_test_graph();
+
+    assert_eq!(shortest_path(&graph, 1, 4), Some((2.0, vec![1, 2, 4])));
+    assert_eq!(shortest_path(&graph, 4, 1), None); // Edges are directed
+    assert_eq!(shortest_path(&graph, 3, 3), Some((0.0, vec![3])));
+    assert_eq!(shortest_path(&graph, 42, 1), None); // Unknown node
+}
+
+#[test]
+fn test_dijkstra_with_custom_cost() {
+    // Count hops over every rating, positive or not
+    let paths = dijkstra_with(&path_test_graph(), 1, |_| Some(1.0));
+
+    assert_eq!(paths.distance_to(4), Some(1.0));
+    assert_eq!(paths.distance_to(5), Some(2.0));
+}
+
+#[test]
+fn test_trust_distance_range() {
+    let rating = |weight| Edge { from: 1, to: 2, weight, timestamp: None };
+
+    assert_eq!(trust_distance(&rating(10.0)), Some(1.0));
+    assert_eq!(trust_distance(&rating(1.0)), Some(10.0));
+    assert_eq!(trust_distance(&rating(0.0)), None);
+    assert_eq!(trust_distance(&rating(-3.0)), None);
+    assert_eq!(trust_distance(&rating(12.0)), None); // Would cost -1 and break Dijkstra
+}
+
+#[test]
+fn test_bfs_distances_and_parents() {
+    // Path 1 - 2 - 3 - 4 plus a node 5 that only has an edge 5 -> 1 pointing in
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 1.0, timestamp: None },
+        Edge { from: 5, to: 1, weight: 1.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    let tree = bfs(&graph, 1, &mut HashSet::new());
+    assert_eq!(tree.distances[&1], 0);
+    assert_eq!(tree.distances[&4], 3);
+    assert_eq!(tree.distances[&5], 1); // Incoming edges are followed too
+    assert_eq!(tree.parents[&3], 2);
+    assert!(!tree.parents.contains_key(&1));
+    assert_eq!(tree.path_to(4), Some(vec![1, 2, 3, 4]));
+    assert_eq!(tree.eccentricity(), 3);
+    assert_eq!(tree.farthest(), 4);
+}
+
+#[test]
+fn test_eccentricity_diameter_radius() {
+    // Path 1 - 2 - 3 - 4 - 5
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 1.0, timestamp: None },
+        Edge { from: 4, to: 5, weight: 1.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    assert_eq!(eccentricity(&graph, 1), 4);
+    assert_eq!(eccentricity(&graph, 3), 2);
+    assert_eq!(diameter_exact(&graph), 4);
+    assert_eq!(radius_exact(&graph), 2);
+    assert_eq!(diameter_double_sweep(&graph), 4);
+    assert_eq!(diameter(&graph), Diameter { value: 4, exact: true });
+}
+
+#[test]
+fn test_diameter_empty_graph() {
+    let graph = Graph::new(&[]);
+
+    assert_eq!(diameter_exact(&graph), 0);
+    assert_eq!(diameter_double_sweep(&graph), 0);
+}
+
+#[test]
+fn test_betweenness_centrality_path() {
+    // 1 -> 2 -> 3 -> 4: node 2 is on paths 1-3 and 1-4, node 3 on 1-4 and 2-4
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 1.0, timestamp: None },
+    ];
+    let betweenness = betweenness_centrality(&Graph::new(&edges), &BetweennessOptions::default());
+
+    assert_eq!(betweenness[&1], 0.0);
+    assert_eq!(betweenness[&2], 2.0);
+    assert_eq!(betweenness[&3], 2.0);
+    assert_eq!(betweenness[&4], 0.0);
+}
+
+#[test]
+fn test_betweenness_centrality_split_paths() {
+    // Two equally short paths 1 -> 2 -> 4 and 1 -> 3 -> 4 share the credit
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 10.0, timestamp: None },
+        Edge { from: 1, to: 3, weight: 10.0, timestamp: None },
+        Edge { from: 2, to: 4, weight: 10.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 1.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    let unweighted = betweenness_centrality(&graph, &BetweennessOptions::default());
+    assert_eq!(unweighted[&2], 0.5);
+    assert_eq!(unweighted[&3], 0.5);
+
+    // With trust distances the path through 3 is much longer
+    let weighted = betweenness_centrality(&graph, &BetweennessOptions { weighted: true, ..Default::default() });
+    assert_eq!(weighted[&2], 1.0);
+    assert_eq!(weighted[&3], 0.0);
+}
+
+#[test]
+fn test_betweenness_centrality_sampled() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 1.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    // Sampling every node is the exact result
+    let options = BetweennessOptions { samples: Some(4), ..Default::default() };
+    assert_eq!(betweenness_centrality(&graph, &options), betweenness_centrality(&graph, &BetweennessOptions::default()));
+
+    // Same seed, same sample
+    let options = BetweennessOptions { samples: Some(2), seed: 7, ..Default::default() };
+    assert_eq!(betweenness_centrality(&graph, &options), betweenness_centrality(&graph, &options));
+    assert_eq!(sample_indices(10, 3, 7).len(), 3);
+}
+
+#[test]
+fn test_closeness_centrality() {
+    // 1 -> 2 -> 3 plus a separate edge 4 -> 5
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 4, to: 5, weight: 1.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    let out_closeness = closeness_centrality(&graph, Direction::Out);
+    assert!((out_closeness[&1] - (2.0 / 4.0) * (2.0 / 3.0)).abs() < 1e-12); // reaches 2 nodes, distances 1 + 2
+    assert!((out_closeness[&4] - (1.0 / 4.0) * 1.0).abs() < 1e-12);
+    assert_eq!(out_closeness[&3], 0.0); // reaches nobody
+
+    let in_closeness = closeness_centrality(&graph, Direction::In);
+    assert_eq!(in_closeness[&1], 0.0);
+    assert!((in_closeness[&3] - out_closeness[&1]).abs() < 1e-12); // mirror image
+}
+
+#[test]
+fn test_harmonic_centrality() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 4, to: 5, weight: 1.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    let out_harmonic = harmonic_centrality(&graph, Direction::Out);
+    assert_eq!(out_harmonic[&1], 1.5); // 1/1 + 1/2
+    assert_eq!(out_harmonic[&4], 1.0);
+    assert_eq!(out_harmonic[&5], 0.0);
+
+    let in_harmonic = harmonic_centrality(&graph, Direction::In);
+    assert_eq!(in_harmonic[&3], 1.5);
+}
+
+#[test]
+fn test_hits() {
+    // 1 and 2 both rate 3 and 4, 5 only rates 3
+    let edges = vec![
+        Edge { from: 1, to: 3, weight: 5.0, timestamp: None },
+        Edge { from: 1, to: 4, weight: 5.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 5.0, timestamp: None },
+        Edge { from: 2, to: 4, weight: -5.0, timestamp: None },
+        Edge { from: 5, to: 3, weight: 5.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    let result = hits(&graph, &HitsOptions::default());
+    assert!((result.authorities.values().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert!((result.hubs.values().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert!(result.authorities[&3] > result.authorities[&4]);
+    assert!((result.hubs[&1] - result.hubs[&2]).abs() < 1e-9);
+    assert!(result.hubs[&1] > result.hubs[&5]);
+    assert_eq!(result.authorities[&1], 0.0); // Nobody rates 1
+    assert_eq!(result.hubs[&3], 0.0); // 3 rates nobody
+
+    // The negative rating 2 -> 4 no longer counts
+    let positive = hits(&graph, &HitsOptions { positive_only: true, ..Default::default() });
+    assert!(positive.hubs[&1] > positive.hubs[&2]);
+}
+
+#[test]
+fn test_core_numbers() {
+    // Cycle 1 -> 2 -> 3 -> 1 with 1 also rating 4
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 1.0, timestamp: None },
+        Edge { from: 1, to: 4, weight: 1.0, timestamp: None },
+        Edge { from: 4, to: 4, weight: 1.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    let total = core_numbers(&graph, DegreeKind::Total);
+    assert_eq!((total[&1], total[&2], total[&3], total[&4]), (2, 2, 2, 1));
+
+    let out = core_numbers(&graph, DegreeKind::Out);
+    assert_eq!((out[&1], out[&2], out[&3], out[&4]), (1, 1, 1, 0)); // The self rating doesn't count
+
+    let incoming = core_numbers(&graph, DegreeKind::In);
+    assert!(incoming.values().all(|&core| core == 1));
+}
+
+#[test]
+fn test_s_core_numbers() {
+    // 1 and 2 rate each other strongly, 3 gets one weak negative rating
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 8.0, timestamp: None },
+        Edge { from: 2, to: 1, weight: 6.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: -2.0, timestamp: None },
+    ];
+    let cores = s_core_numbers(&Graph::new(&edges), DegreeKind::Total);
+
+    assert_eq!(cores[&3], 2.0);
+    assert_eq!(cores[&1], 14.0);
+    assert_eq!(cores[&2], 14.0); // 16 before 3 is removed, but only 14 inside the core
+}
+
+#[test]
+fn test_triangle_stats() {
+    // Triangle 1, 2, 3 where 1 and 2 rate each other, plus 1 -> 4
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 1, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 1.0, timestamp: None },
+        Edge { from: 1, to: 4, weight: 1.0, timestamp: None },
+    ];
+    let stats = triangle_stats(&Graph::new(&edges));
+
+    assert_eq!(stats.total_triangles, 1);
+    assert_eq!((stats.triangles[&1], stats.triangles[&4]), (1, 0));
+    assert!((stats.transitivity - 3.0 / 5.0).abs() < 1e-12);
+    assert!((stats.local_clustering[&1] - 1.0 / 3.0).abs() < 1e-12);
+    assert_eq!(stats.local_clustering[&2], 1.0);
+    assert!((stats.average_clustering - 7.0 / 12.0).abs() < 1e-12);
+
+    // 1's neighbours 2, 3 and 4 have the single rating 2 -> 3 between them, out of 6
+    let graph = Graph::new(&edges);
+    for node in 1..=4 {
+        assert!((stats.rating_clustering[&node] - graph.clustering_coefficient(node)).abs() < 1e-12);
+    }
+    assert!((stats.rating_clustering[&1] - 1.0 / 6.0).abs() < 1e-12);
+
+    // Fagiolo: 1 has 4 ratings with one reciprocal pair, so the 2 directed triangles are out of 10
+    assert!((stats.directed_clustering[&1] - 0.2).abs() < 1e-12);
+    assert_eq!(stats.directed_clustering[&2], 0.5);
+    assert_eq!(stats.directed_clustering[&3], 1.0);
+    assert_eq!(stats.directed_clustering[&4], 0.0);
+    assert!((stats.average_directed_clustering - 0.425).abs() < 1e-12);
+}
+
+#[test]
+fn test_triangle_stats_matches_balance_count() {
+    // Every pair of 1..=5 linked one way, so every triple is a triangle
+    let mut edges = Vec::new();
+    for from in 1..=5 {
+        for to in from + 1..=5 {
+            edges.push(Edge { from, to, weight: 1.0, timestamp: None });
+        }
+    }
+    let graph = Graph::new(&edges);
+    let stats = triangle_stats(&graph);
+
+    assert_eq!(stats.total_triangles, 10);
+    assert_eq!(stats.total_triangles, crate::graph::balance::structural_balance(&graph).total());
+    assert_eq!(stats.transitivity, 1.0);
+    assert!(stats.triangles.values().all(|&count| count == 6));
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::graph::Network;
+#[cfg(test)]
+use crate::graph::{Edge, Graph};
+
+// Signed triangles of the undirected projection of the graph, for
+// structural balance. Two nodes are connected if they rated each other in
+// either direction, and the sign of the connection is the sign of the sum of
+// those ratings (pairs whose ratings cancel out to 0 are left out).
+// A triangle is balanced if it has an even number of negative sides,
+// i.e. "the friend of my friend is my friend" and "the enemy of my enemy is my friend".
+#[derive(Debug, Clone)]
+pub struct StructuralBalance {
+    pub counts: [usize; 4],                     // Triangles with 0, 1, 2 and 3 negative sides: +++, ++-, +--, ---
+    pub node_triangles: HashMap<usize, usize>,  // Triangles each node is part of
+    pub node_balanced: HashMap<usize, usize>,   // Balanced triangles each node is part of
+}
+
+impl StructuralBalance {
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    // +++ and +-- triangles
+    pub fn balanced(&self) -> usize {
+        self.counts[0] + self.counts[2]
+    }
+
+    // ++- and --- triangles
+    pub fn unbalanced(&self) -> usize {
+        self.counts[1] + self.counts[3]
+    }
+
+    // Share of the triangles that are balanced, None if there are no triangles.
+    pub fn balance_ratio(&self) -> Option<f64> {
+        if self.total() == 0 {
+            return None;
+        }
+        Some(self.balanced() as f64 / self.total() as f64)
+    }
+
+    // Share of the triangles of node that are balanced, None if it isn't in any triangle.
+    pub fn node_balance(&self, node: usize) -> Option<f64> {
+        let triangles = *self.node_triangles.get(&node)?;
+        Some(*self.node_balanced.get(&node).unwrap_or(&0) as f64 / triangles as f64)
+    }
+
+    // node_balance of every node that is in at least one triangle.
+    pub fn balance_participation(&self) -> HashMap<usize, f64> {
+        self.node_triangles.keys()
+            .filter_map(|&node| Some((node, self.node_balance(node)?)))
+            .collect()
+    }
+}
+
+impl fmt::Display for StructuralBalance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let labels = ["+++", "++-", "+--", "---"];
+        for (label, count) in labels.iter().zip(self.counts.iter()) {
+            writeln!(f, "{}: {}", label, count)?;
+        }
+        match self.balance_ratio() {
+            Some(ratio) => write!(f, "Balanced triangles: {} of {} ({:.2}%)", self.balanced(), self.total(), ratio * 100.0),
+            None => write!(f, "Balanced triangles: no triangles"),
+        }
+    }
+}
+
+// Count the signed triangles of the graph, see StructuralBalance.
+// Uses the same neighbour sets as Network::clustering_coefficient.
+pub fn structural_balance<G: Network>(graph: &G) -> StructuralBalance {
+    // Sign of every connected pair, keyed by (smaller node, larger node)
+    let mut pair_weights: HashMap<(usize, usize), f64> = HashMap::new();
+    for edge in graph.edges() {
+        if edge.from != edge.to {
+            *pair_weights.entry((edge.from.min(edge.to), edge.from.max(edge.to))).or_insert(0.0) += edge.weight;
+        }
+    }
+    let is_positive = |a: usize, b: usize| -> Option<bool> {
+        let weight = *pair_weights.get(&(a.min(b), a.max(b)))?;
+        if weight == 0.0 { None } else { Some(weight > 0.0) }
+    };
+
+    let mut balance = StructuralBalance { counts: [0; 4], node_triangles: HashMap::new(), node_balanced: HashMap::new() };
+
+    // Every triangle u < v < w is found once, from u
+    for u in graph.nodes() {
+        let mut larger: Vec<usize> = graph.neighbor_set(u).into_iter().filter(|&nb| nb > u).collect();
+        larger.sort();
+
+        for (i, &v) in larger.iter().enumerate() {
+            for &w in &larger[i + 1..] {
+                let signs = match (is_positive(u, v), is_positive(u, w), is_positive(v, w)) {
+                    (Some(uv), Some(uw), Some(vw)) => [uv, uw, vw],
+                    _ => continue,
+                };
+                let negative_sides = signs.iter().filter(|&&positive| !positive).count();
+                balance.counts[negative_sides] += 1;
+
+                for node in [u, v, w] {
+                    *balance.node_triangles.entry(node).or_insert(0) += 1;
+                    if negative_sides % 2 == 0 {
+                        *balance.node_balanced.entry(node).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    balance
+}
+
+// The 16 triad types of the directed triad census, in the usual order.
+pub const TRIAD_TYPES: [&str; 16] = [
+    "003", "012", "102", "021D", "021U", "021C", "111D", "111U",
+    "030T", "030C", "201", "120D", "120U", "120C", "210", "300",
+];
+
+// Triad type (1 based index in TRIAD_TYPES) of each of the 64 ways to link 3 nodes, see tricode.
+const TRICODES: [usize; 64] = [
+    1, 2, 2, 3, 2, 4, 6, 8, 2, 6, 5, 7, 3, 8, 7, 11,
+    2, 6, 4, 8, 5, 9, 9, 13, 6, 10, 9, 14, 7, 14, 12, 15,
+    2, 5, 6, 7, 6, 9, 10, 14, 4, 9, 9, 12, 8, 13, 14, 15,
+    3, 7, 8, 11, 7, 12, 14, 15, 8, 14, 13, 15, 11, 15, 15, 16,
+];
+
+// Number of triads (sets of 3 nodes) of each type, ignoring signs and weights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriadCensus {
+    pub counts: [u64; 16],      // In the order of TRIAD_TYPES
+}
+
+impl TriadCensus {
+    // Count of one triad type by name, e.g. "030T".
+    pub fn count(&self, triad_type: &str) -> Option<u64> {
+        let index = TRIAD_TYPES.iter().position(|&name| name == triad_type)?;
+        Some(self.counts[index])
+    }
+}
+
+impl fmt::Display for TriadCensus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, (name, count)) in TRIAD_TYPES.iter().zip(self.counts.iter()).enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{:>4}: {}", name, count)?;
+        }
+        Ok(())
+    }
+}
+
+// Directed triad census with the algorithm of Batagelj and Mrvar (2001),
+// which only looks at connected pairs so it doesn't need to go through all
+// n^3 triads. The empty triads (003) are what is left of the n choose 3.
+pub fn triad_census<G: Network>(graph: &G) -> TriadCensus {
+    let links: HashSet<(usize, usize)> = graph.edges()
+        .filter(|edge| edge.from != edge.to)
+        .map(|edge| (edge.from, edge.to))
+        .collect();
+    let neighbors: HashMap<usize, HashSet<usize>> = graph.nodes().map(|node| {
+            let mut nb_set = graph.neighbor_set(node);
+            nb_set.remove(&node);
+            (node, nb_set)
+        })
+        .collect();
+
+    let num_nodes = graph.node_count() as u64;
+    let mut counts = [0u64; 16];
+
+    for (&v, v_neighbors) in &neighbors {
+        for &u in v_neighbors {
+            if u <= v {
+                continue;
+            }
+            let mut third_nodes: HashSet<usize> = v_neighbors.union(&neighbors[&u]).copied().collect();
+            third_nodes.remove(&u);
+            third_nodes.remove(&v);
+
+            // Triads where the third node is linked to neither u nor v
+            let dyad = if links.contains(&(v, u)) && links.contains(&(u, v)) { 2 } else { 1 };
+            counts[dyad] += num_nodes - third_nodes.len() as u64 - 2;
+
+            // Count each connected triad only once
+            for &w in &third_nodes {
+                if u < w || (v < w && w < u && !v_neighbors.contains(&w)) {
+                    counts[TRICODES[tricode(&links, v, u, w)] - 1] += 1;
+                }
+            }
+        }
+    }
+
+    let all_triads = if num_nodes < 3 { 0 } else { num_nodes * (num_nodes - 1) * (num_nodes - 2) / 6 };
+    counts[0] = all_triads - counts[1..].iter().sum::<u64>();
+
+    TriadCensus { counts }
+}
+
+// helper that encodes which of the 6 possible links between v, u and w exist as a number in 0..64
+fn tricode(links: &HashSet<(usize, usize)>, v: usize, u: usize, w: usize) -> usize {
+    [(v, u, 1), (u, v, 2), (v, w, 4), (w, v, 8), (u, w, 16), (w, u, 32)].iter()
+        .filter(|(from, to, _)| links.contains(&(*from, *to)))
+        .map(|(_, _, bit)| bit)
+        .sum()
+}
+
+// ----------------------- TESTS -----------------------
+
+#[test]
+fn test_structural_balance() {
+    // 1, 2, 3 are friends (+++), 1 and 2 both distrust 4 (+--),
+    // 3 trusts 4 which makes 1, 3, 4 and 2, 3, 4 unbalanced (++-)
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 5.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 5.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 5.0, timestamp: None },
+        Edge { from: 1, to: 4, weight: -5.0, timestamp: None },
+        Edge { from: 4, to: 2, weight: -5.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 5.0, timestamp: None },
+    ];
+    let balance = structural_balance(&Graph::new(&edges));
+
+    assert_eq!(balance.counts, [1, 2, 1, 0]);
+    assert_eq!(balance.total(), 4);
+    assert_eq!(balance.balance_ratio(), Some(0.5));
+    assert_eq!(balance.node_balance(1), Some(2.0 / 3.0));
+    assert_eq!(balance.node_balance(3), Some(1.0 / 3.0));
+    assert_eq!(balance.node_balance(42), None);
+    assert_eq!(balance.balance_participation().len(), 4);
+}
+
+#[test]
+fn test_structural_balance_reciprocal_ratings() {
+    // 1 -> 2 is positive overall, 1 and 3 cancel out so there is no triangle
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 5.0, timestamp: None },
+        Edge { from: 2, to: 1, weight: -1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: -2.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 2.0, timestamp: None },
+        Edge { from: 1, to: 3, weight: -2.0, timestamp: None },
+    ];
+    let balance = structural_balance(&Graph::new(&edges));
+
+    assert_eq!(balance.total(), 0);
+    assert_eq!(balance.balance_ratio(), None);
+}
+
+#[test]
+fn test_triad_census() {
+    // A cycle 1 -> 2 -> 3 -> 1, plus 4 that only rates 1
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 1.0, timestamp: None },
+        Edge { from: 4, to: 1, weight: 1.0, timestamp: None },
+    ];
+    let census = triad_census(&Graph::new(&edges));
+
+    assert_eq!(census.count("030C"), Some(1));  // 1, 2, 3
+    assert_eq!(census.count("021U"), Some(1));  // 3 -> 1 <- 4
+    assert_eq!(census.count("021C"), Some(1));  // 4 -> 1 -> 2
+    assert_eq!(census.count("012"), Some(1));   // 2 -> 3 and 4
+    assert_eq!(census.count("003"), Some(0));
+    assert_eq!(census.counts.iter().sum::<u64>(), 4); // 4 choose 3
+    assert_eq!(census.count("999"), None);
+}
+
+#[test]
+fn test_triad_census_mutual_and_empty() {
+    // A mutual pair 1 <-> 2 and a separate rating 3 -> 4
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 1, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 1.0, timestamp: None },
+    ];
+    let census = triad_census(&Graph::new(&edges));
+
+    assert_eq!(census.count("102"), Some(2));   // 1 <-> 2 with 3 or 4
+    assert_eq!(census.count("012"), Some(2));   // 3 -> 4 with 1 or 2
+    assert_eq!(census.count("003"), Some(0));
+}
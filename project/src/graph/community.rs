@@ -0,0 +1,577 @@
+use std::collections::HashMap;
+
+use crate::graph::Edge;
+use crate::graph::Graph;
+use crate::graph::Network;
+
+// Options for louvain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LouvainOptions {
+    pub resolution: f64,        // Higher values give more, smaller communities
+}
+
+impl Default for LouvainOptions {
+    fn default() -> LouvainOptions {
+        LouvainOptions { resolution: 1.0 }
+    }
+}
+
+// A split of the nodes into communities, community ids are 0..len() with
+// community 0 the largest. modularity is the signed modularity for signed_louvain.
+#[derive(Debug, Clone)]
+pub struct Partition {
+    pub community_of: HashMap<usize, usize>,
+    pub modularity: f64,
+}
+
+impl Partition {
+    // Number of communities.
+    pub fn len(&self) -> usize {
+        self.community_of.values().max().map(|&max| max + 1).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.community_of.is_empty()
+    }
+
+    // Nodes of every community (sorted), indexed by community id.
+    pub fn communities(&self) -> Vec<Vec<usize>> {
+        let mut communities: Vec<Vec<usize>> = vec![Vec::new(); self.len()];
+        for (&node, &community) in &self.community_of {
+            communities[community].push(node);
+        }
+        for members in communities.iter_mut() {
+            members.sort();
+        }
+        communities
+    }
+
+    // One subgraph per community, indexed by community id like
+    // Network::find_subgraphs. Each subgraph only keeps the ratings between its own nodes.
+    pub fn subgraphs<G: Network>(&self, graph: &G) -> Vec<Graph> {
+        let mut contents: Vec<HashMap<usize, Vec<Edge>>> = vec![HashMap::new(); self.len()];
+        for (&node, &community) in &self.community_of {
+            let edges: Vec<Edge> = graph.out_edges(node)
+                .filter(|edge| self.community_of.get(&edge.to) == Some(&community))
+                .collect();
+            contents[community].insert(node, edges);
+        }
+        contents.into_iter().map(Graph::from_content).collect()
+    }
+
+    // Ratings that go against this partition.
+    pub fn frustration<G: Network>(&self, graph: &G) -> Frustration {
+        frustration(graph, &self.community_of)
+    }
+}
+
+// Ratings that go against a split of the nodes into groups: positive ratings
+// between groups and negative ratings inside one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustration {
+    pub positive_between: usize,
+    pub negative_within: usize,
+}
+
+impl Frustration {
+    pub fn total(&self) -> usize {
+        self.positive_between + self.negative_within
+    }
+}
+
+// Whether a rating is counted by frustration: self ratings and ratings of 0 aren't.
+pub fn counts_for_frustration(edge: &Edge) -> bool {
+    edge.from != edge.to && edge.weight != 0.0
+}
+
+// Frustration of any split of the nodes, e.g. the weak components.
+// Nodes missing from community_of count as alone in their own group.
+pub fn frustration<G: Network>(graph: &G, community_of: &HashMap<usize, usize>) -> Frustration {
+    let mut result = Frustration { positive_between: 0, negative_within: 0 };
+    for edge in graph.edges() {
+        if !counts_for_frustration(&edge) {
+            continue;
+        }
+        let same_group = match (community_of.get(&edge.from), community_of.get(&edge.to)) {
+            (Some(from), Some(to)) => from == to,
+            _ => false,
+        };
+        if edge.weight > 0.0 && !same_group {
+            result.positive_between += 1;
+        } else if edge.weight < 0.0 && same_group {
+            result.negative_within += 1;
+        }
+    }
+    result
+}
+
+// Undirected weighted graph on dense indices, the form Louvain works on.
+// A self loop stands for the links inside a community once it is aggregated.
+#[derive(Debug, Clone)]
+struct WeightedGraph {
+    neighbors: Vec<Vec<(usize, f64)>>,      // Both directions of every link, no self loops
+    self_loops: Vec<f64>,
+}
+
+impl WeightedGraph {
+    // Sum of the link weights at node, self loops count twice.
+    fn degree(&self, node: usize) -> f64 {
+        self.neighbors[node].iter().map(|(_, weight)| weight).sum::<f64>() + 2.0 * self.self_loops[node]
+    }
+
+    // Graph with one node per community, summing the links between communities.
+    fn aggregate(&self, membership: &[usize], num_communities: usize) -> WeightedGraph {
+        let mut links: Vec<HashMap<usize, f64>> = vec![HashMap::new(); num_communities];
+        let mut self_loops = vec![0.0; num_communities];
+
+        for (node, neighbors) in self.neighbors.iter().enumerate() {
+            let community = membership[node];
+            self_loops[community] += self.self_loops[node];
+            for &(neighbor, weight) in neighbors {
+                if membership[neighbor] == community {
+                    self_loops[community] += weight / 2.0; // Seen from both ends
+                } else {
+                    *links[community].entry(membership[neighbor]).or_insert(0.0) += weight;
+                }
+            }
+        }
+
+        let neighbors = links.into_iter()
+            .map(|community_links| {
+                let mut community_links: Vec<(usize, f64)> = community_links.into_iter().collect();
+                community_links.sort_by_key(|&(community, _)| community);
+                community_links
+            })
+            .collect();
+        WeightedGraph { neighbors, self_loops }
+    }
+}
+
+// One sign of links Louvain works with. Links of a layer with sign 1 count
+// for putting their nodes together and links of a layer with sign -1 count
+// against it, which gives the signed modularity of Gomez et al. (2009).
+#[derive(Debug, Clone)]
+struct Layer {
+    graph: WeightedGraph,
+    sign: f64,
+}
+
+impl Layer {
+    fn aggregate(&self, membership: &[usize], num_communities: usize) -> Layer {
+        Layer { graph: self.graph.aggregate(membership, num_communities), sign: self.sign }
+    }
+}
+
+// Undirected projection of the ratings that `keep` accepts: two nodes are
+// linked if either rated the other, with the sum of the absolute ratings as
+// the weight.
+fn projection<G: Network, F: Fn(&Edge) -> bool>(graph: &G, nodes: &[usize], keep: F) -> WeightedGraph {
+    let index_of: HashMap<usize, usize> = nodes.iter().enumerate().map(|(index, &node)| (node, index)).collect();
+    let mut links: Vec<HashMap<usize, f64>> = vec![HashMap::new(); nodes.len()];
+
+    for edge in nodes.iter().flat_map(|&node| graph.out_edges(node)) {
+        if keep(&edge) && edge.from != edge.to {
+            let (from, to) = (index_of[&edge.from], index_of[&edge.to]);
+            *links[from].entry(to).or_insert(0.0) += edge.weight.abs();
+            *links[to].entry(from).or_insert(0.0) += edge.weight.abs();
+        }
+    }
+
+    let neighbors = links.into_iter()
+        .map(|node_links| {
+            let mut node_links: Vec<(usize, f64)> = node_links.into_iter().collect();
+            node_links.sort_by_key(|&(neighbor, _)| neighbor);
+            node_links
+        })
+        .collect();
+    WeightedGraph { neighbors, self_loops: vec![0.0; nodes.len()] }
+}
+
+// Layers for positive communities, only the positive ratings.
+fn positive_layers<G: Network>(graph: &G, nodes: &[usize]) -> Vec<Layer> {
+    vec![Layer { graph: projection(graph, nodes, |edge| edge.weight > 0.0), sign: 1.0 }]
+}
+
+// Layers for signed communities, positive ratings pull nodes together and
+// negative ratings push them apart.
+fn signed_layers<G: Network>(graph: &G, nodes: &[usize]) -> Vec<Layer> {
+    vec![
+        Layer { graph: projection(graph, nodes, |edge| edge.weight > 0.0), sign: 1.0 },
+        Layer { graph: projection(graph, nodes, |edge| edge.weight < 0.0), sign: -1.0 },
+    ]
+}
+
+// Louvain community detection (Blondel et al. 2008) on the positive
+// projection of the graph. Nodes are moved to the neighbouring community that
+// increases modularity the most until nothing moves, then every community is
+// merged into one node and this repeats on the smaller graph. Nodes are visited
+// in id order so the result is the same on every run. Nodes without positive
+// ratings end up alone in their own community.
+pub fn louvain<G: Network>(graph: &G, options: &LouvainOptions) -> Partition {
+    run_louvain(graph, positive_layers, options.resolution)
+}
+
+// Louvain on the signed graph, maximizing signed modularity: the modularity
+// of the positive ratings minus that of the negative ratings, weighted by
+// their share of the total weight. Negative ratings tend to end up between
+// communities, see Partition::frustration. The modularity of the result is
+// the signed modularity.
+pub fn signed_louvain<G: Network>(graph: &G, options: &LouvainOptions) -> Partition {
+    run_louvain(graph, signed_layers, options.resolution)
+}
+
+// Modularity of a split of the nodes on the positive projection of the graph.
+// Nodes missing from community_of count as alone in their own community.
+pub fn modularity<G: Network>(graph: &G, community_of: &HashMap<usize, usize>, resolution: f64) -> f64 {
+    partition_modularity(graph, community_of, positive_layers, resolution)
+}
+
+// Signed modularity of a split of the nodes, see signed_louvain.
+pub fn signed_modularity<G: Network>(graph: &G, community_of: &HashMap<usize, usize>, resolution: f64) -> f64 {
+    partition_modularity(graph, community_of, signed_layers, resolution)
+}
+
+// Both Louvain variants, on the layers make_layers builds.
+fn run_louvain<G: Network>(graph: &G, make_layers: fn(&G, &[usize]) -> Vec<Layer>, resolution: f64) -> Partition {
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort();
+    let layers = make_layers(graph, &nodes);
+
+    // membership[i] is the community of nodes[i]
+    let mut membership: Vec<usize> = (0..nodes.len()).collect();
+    let mut level = layers.clone();
+    loop {
+        let (mut level_membership, moved) = move_nodes(&level, resolution);
+        if !moved {
+            break;
+        }
+        let num_communities = renumber(&mut level_membership);
+        for community in membership.iter_mut() {
+            *community = level_membership[*community];
+        }
+        level = level.iter().map(|layer| layer.aggregate(&level_membership, num_communities)).collect();
+    }
+
+    partition_from(&nodes, membership, &layers, resolution)
+}
+
+// helper for modularity and signed_modularity
+fn partition_modularity<G: Network>(graph: &G, community_of: &HashMap<usize, usize>,
+                        make_layers: fn(&G, &[usize]) -> Vec<Layer>, resolution: f64) -> f64 {
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort();
+    let layers = make_layers(graph, &nodes);
+
+    // Communities numbered by first appearance, a node missing from
+    // community_of gets a new number of its own
+    let mut new_ids: HashMap<usize, usize> = HashMap::new();
+    let mut num_communities = 0;
+    let mut membership: Vec<usize> = Vec::with_capacity(nodes.len());
+    for node in &nodes {
+        let community = match community_of.get(node) {
+            Some(&community) => *new_ids.entry(community).or_insert(num_communities),
+            None => num_communities,
+        };
+        if community == num_communities {
+            num_communities += 1;
+        }
+        membership.push(community);
+    }
+    layered_modularity(&layers, &membership, resolution)
+}
+
+// One level of Louvain: local moves until no node changes community.
+// Returns the community of every node and whether any node moved.
+fn move_nodes(layers: &[Layer], resolution: f64) -> (Vec<usize>, bool) {
+    let num_nodes = layers[0].graph.neighbors.len();
+    let degrees: Vec<Vec<f64>> = layers.iter()
+        .map(|layer| (0..num_nodes).map(|node| layer.graph.degree(node)).collect())
+        .collect();
+    let total_weights: Vec<f64> = degrees.iter().map(|layer_degrees| layer_degrees.iter().sum()).collect();     // 2m of each layer
+    let mut membership: Vec<usize> = (0..num_nodes).collect();
+    if total_weights.iter().all(|&total_weight| total_weight == 0.0) {
+        return (membership, false);
+    }
+
+    // Sum of the degrees in each community, per layer, and the ids no node uses
+    let mut community_degrees = degrees.clone();
+    let mut sizes = vec![1usize; num_nodes];
+    let mut empty: Vec<usize> = Vec::new();
+    let mut links_to: HashMap<usize, f64> = HashMap::new();
+    let mut moved = false;
+
+    loop {
+        let mut improved = false;
+        for node in 0..num_nodes {
+            let current = membership[node];
+            for (community_degree, layer_degrees) in community_degrees.iter_mut().zip(degrees.iter()) {
+                community_degree[current] -= layer_degrees[node];
+            }
+            sizes[current] -= 1;
+
+            // Signed link weight from node to each neighbouring community
+            links_to.clear();
+            links_to.insert(current, 0.0);
+            for layer in layers {
+                for &(neighbor, weight) in &layer.graph.neighbors[node] {
+                    *links_to.entry(membership[neighbor]).or_insert(0.0) += layer.sign * weight;
+                }
+            }
+
+            // Modularity gain of joining a community, up to a constant factor.
+            // Ties keep the current community, then go to the lowest id.
+            let gain = |community: usize, links: f64| {
+                let expected: f64 = (0..layers.len())
+                    .filter(|&layer| total_weights[layer] > 0.0)
+                    .map(|layer| layers[layer].sign * community_degrees[layer][community] * degrees[layer][node] / total_weights[layer])
+                    .sum();
+                links - resolution * expected
+            };
+            let mut best = (current, gain(current, links_to[&current]));
+            let mut candidates: Vec<(usize, f64)> = links_to.iter().map(|(&community, &links)| (community, links)).collect();
+            // An empty community has gain 0, so a node whose links to every
+            // community are negative enough can end up on its own
+            let fresh = if sizes[current] == 0 { None } else { empty.last().copied() };
+            candidates.extend(fresh.map(|community| (community, 0.0)));
+            candidates.sort_by_key(|&(community, _)| community);
+            for (community, links) in candidates {
+                let community_gain = gain(community, links);
+                if community_gain > best.1 + 1e-12 {
+                    best = (community, community_gain);
+                }
+            }
+
+            for (community_degree, layer_degrees) in community_degrees.iter_mut().zip(degrees.iter()) {
+                community_degree[best.0] += layer_degrees[node];
+            }
+            sizes[best.0] += 1;
+            if Some(best.0) == fresh {
+                empty.pop();
+            }
+            if sizes[current] == 0 && best.0 != current {
+                empty.push(current);
+            }
+            if best.0 != current {
+                membership[node] = best.0;
+                improved = true;
+                moved = true;
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    (membership, moved)
+}
+
+// Modularity of membership over the layers. For one layer this is
+// sum over communities of links inside / 2m - resolution * (degree / 2m)^2,
+// with more layers each adds or removes its own modularity weighted by its 2m.
+fn layered_modularity(layers: &[Layer], membership: &[usize], resolution: f64) -> f64 {
+    let num_communities = membership.iter().max().map(|&max| max + 1).unwrap_or(0);
+    let mut modularity = 0.0;
+    let mut all_weight = 0.0;
+
+    for layer in layers {
+        let mut inside = vec![0.0; num_communities];
+        let mut degree = vec![0.0; num_communities];
+        let mut total_weight = 0.0;
+
+        for (node, neighbors) in layer.graph.neighbors.iter().enumerate() {
+            let community = membership[node];
+            let node_degree = layer.graph.degree(node);
+            degree[community] += node_degree;
+            total_weight += node_degree;
+            inside[community] += 2.0 * layer.graph.self_loops[node];
+            for &(neighbor, weight) in neighbors {
+                if membership[neighbor] == community {
+                    inside[community] += weight;
+                }
+            }
+        }
+
+        if total_weight > 0.0 {
+            modularity += layer.sign * (0..num_communities)
+                .map(|community| inside[community] - resolution * degree[community].powi(2) / total_weight)
+                .sum::<f64>();
+            all_weight += total_weight;
+        }
+    }
+
+    if all_weight == 0.0 {
+        return 0.0;
+    }
+    modularity / all_weight
+}
+
+// helper that renumbers community ids to 0..count in order of first
+// appearance, returns count
+fn renumber(membership: &mut [usize]) -> usize {
+    let mut new_ids: HashMap<usize, usize> = HashMap::new();
+    for community in membership.iter_mut() {
+        let next_id = new_ids.len();
+        *community = *new_ids.entry(*community).or_insert(next_id);
+    }
+    new_ids.len()
+}
+
+// helper that builds the Partition of the original nodes, with the
+// communities numbered from largest to smallest (ties by smallest node)
+fn partition_from(nodes: &[usize], mut membership: Vec<usize>, layers: &[Layer], resolution: f64) -> Partition {
+    let num_communities = renumber(&mut membership);
+    let modularity = layered_modularity(layers, &membership, resolution);
+
+    let mut sizes = vec![0usize; num_communities];
+    for &community in &membership {
+        sizes[community] += 1;
+    }
+    // renumber numbers by first appearance, which is by smallest node since nodes are sorted
+    let mut order: Vec<usize> = (0..num_communities).collect();
+    order.sort_by_key(|&community| (std::cmp::Reverse(sizes[community]), community));
+    let mut rank = vec![0; num_communities];
+    for (position, &community) in order.iter().enumerate() {
+        rank[community] = position;
+    }
+
+    let community_of = nodes.iter().zip(membership.iter()).map(|(&node, &community)| (node, rank[community])).collect();
+    Partition { community_of, modularity }
+}
+
+// ----------------------- TESTS -----------------------
+
+#[cfg(test)]
+fn two_cliques() -> Vec<Edge> {
+    // Cliques 1..=4 and 5..=8 joined by a single weak rating 4 -> 5,
+    // and a negative rating 1 -> 8 that the projection ignores
+    let mut edges = Vec::new();
+    for group in [[1, 2, 3, 4], [5, 6, 7, 8]] {
+        for &from in &group {
+            for &to in &group {
+                if from < to {
+                    edges.push(Edge { from, to, weight: 5.0, timestamp: None });
+                }
+            }
+        }
+    }
+    edges.push(Edge { from: 4, to: 5, weight: 1.0, timestamp: None });
+    edges.push(Edge { from: 1, to: 8, weight: -10.0, timestamp: None });
+    edges
+}
+
+#[test]
+fn test_louvain_two_cliques() {
+    let graph = Graph::new(&two_cliques());
+    let partition = louvain(&graph, &LouvainOptions::default());
+
+    assert_eq!(partition.len(), 2);
+    assert_eq!(partition.communities(), vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]]);
+    assert!(partition.modularity > 0.4);
+    assert!((partition.modularity - modularity(&graph, &partition.community_of, 1.0)).abs() < 1e-12);
+}
+
+#[test]
+fn test_modularity() {
+    let graph = Graph::new(&two_cliques());
+
+    // Everything in one community has modularity 0
+    let together: HashMap<usize, usize> = graph.nodes().map(|node| (node, 0)).collect();
+    assert!(modularity(&graph, &together, 1.0).abs() < 1e-12);
+
+    // A worse split scores lower than Louvain's
+    let mixed: HashMap<usize, usize> = graph.nodes().map(|node| (node, node % 2)).collect();
+    assert!(modularity(&graph, &mixed, 1.0) < louvain(&graph, &LouvainOptions::default()).modularity);
+}
+
+#[test]
+fn test_modularity_missing_nodes() {
+    let graph = Graph::new(&two_cliques());
+
+    // Nodes left out are alone, whatever ids the others use
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort();
+    let alone: HashMap<usize, usize> = nodes.iter().map(|&node| (node, node)).collect();
+    // Node 1 is left out and node 2 gets id usize::MAX
+    let large_ids: HashMap<usize, usize> = nodes[1..].iter().map(|&node| (node, usize::MAX - (node - 2))).collect();
+    let expected = modularity(&graph, &alone, 1.0);
+    assert!((modularity(&graph, &large_ids, 1.0) - expected).abs() < 1e-12);
+    assert!((modularity(&graph, &HashMap::new(), 1.0) - expected).abs() < 1e-12);
+}
+
+#[test]
+fn test_louvain_isolated_nodes() {
+    // 3 only has a negative rating so it is alone
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 5.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: -5.0, timestamp: None },
+    ];
+    let partition = louvain(&Graph::new(&edges), &LouvainOptions::default());
+
+    assert_eq!(partition.communities(), vec![vec![1, 2], vec![3]]);
+    assert_eq!(partition.modularity, 0.0);
+    assert!(louvain(&Graph::new(&[]), &LouvainOptions::default()).is_empty());
+}
+
+#[test]
+fn test_signed_louvain_separates_enemies() {
+    // Two positive groups 1..=3 and 4..=6 that distrust each other,
+    // with a single positive rating 3 -> 4 across
+    let mut edges = Vec::new();
+    for group in [[1, 2, 3], [4, 5, 6]] {
+        for &from in &group {
+            for &to in &group {
+                if from != to {
+                    edges.push(Edge { from, to, weight: 2.0, timestamp: None });
+                }
+            }
+        }
+    }
+    for from in 1..=3 {
+        for to in 4..=6 {
+            edges.push(Edge { from: to, to: from, weight: -10.0, timestamp: None });
+        }
+    }
+    edges.push(Edge { from: 3, to: 4, weight: 10.0, timestamp: None });
+    let graph = Graph::new(&edges);
+
+    let partition = signed_louvain(&graph, &LouvainOptions::default());
+    assert_eq!(partition.communities(), vec![vec![1, 2, 3], vec![4, 5, 6]]);
+    assert!((partition.modularity - signed_modularity(&graph, &partition.community_of, 1.0)).abs() < 1e-12);
+    assert_eq!(partition.frustration(&graph), Frustration { positive_between: 1, negative_within: 0 });
+
+    // Everything is one weak component, so all the negative ratings are inside it
+    let components: HashMap<usize, usize> = graph.nodes().map(|node| (node, 0)).collect();
+    assert_eq!(frustration(&graph, &components).total(), 9);
+    assert!(signed_modularity(&graph, &components, 1.0) < partition.modularity);
+}
+
+#[test]
+fn test_partition_subgraphs() {
+    let graph = Graph::new(&two_cliques());
+    let partition = louvain(&graph, &LouvainOptions::default());
+    let subgraphs = partition.subgraphs(&graph);
+
+    assert_eq!(subgraphs.len(), 2);
+    assert_eq!(subgraphs[0].node_count(), 4);
+    assert_eq!(subgraphs[0].out_degree(4), 0); // 4 -> 5 is between communities
+    assert_eq!(subgraphs[0].in_degree(4), 3);
+    assert_eq!(partition.frustration(&graph), Frustration { positive_between: 1, negative_within: 0 });
+}
+
+#[test]
+fn test_signed_louvain_isolates_node() {
+    // 4 ends up next to 1, 2 and 3 while most of its ratings with them are
+    // negative; it can only leave that community by moving to an empty one
+    let ratings = [(1, 2, 3.0), (1, 3, 1.0), (1, 4, -1.0), (1, 6, -3.0), (2, 3, 10.0),
+                   (2, 4, 2.0), (3, 4, -1.0), (3, 5, -2.0), (4, 5, -2.0)];
+    let edges: Vec<Edge> = ratings.iter().map(|&(from, to, weight)| Edge { from, to, weight, timestamp: None }).collect();
+    let graph = Graph::new(&edges);
+
+    let partition = signed_louvain(&graph, &LouvainOptions::default());
+    assert_eq!(partition.communities(), vec![vec![1, 2, 3], vec![4], vec![5], vec![6]]);
+    assert!((partition.modularity - signed_modularity(&graph, &partition.community_of, 1.0)).abs() < 1e-12);
+
+    let mut joined = partition.community_of.clone();
+    joined.insert(4, partition.community_of[&1]);
+    assert!(signed_modularity(&graph, &joined, 1.0) < partition.modularity);
+}
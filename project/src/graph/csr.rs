@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::graph::Edge;
+use crate::graph::Graph;
+use crate::graph::Network;
+
+// Maps the external node ids of the dataset to dense u32 indices
+// (0..number of nodes) and back.
+#[derive(Debug, Clone, Default)]
+pub struct NodeInterner {
+    dense: HashMap<usize, u32>,     // external id -> dense index
+    external: Vec<usize>,           // dense index -> external id
+}
+
+impl NodeInterner {
+    pub fn new() -> NodeInterner {
+        NodeInterner::default()
+    }
+
+    // Dense index of a node, adding it if it hasn't been seen yet.
+    pub fn intern(&mut self, node: usize) -> u32 {
+        if let Some(&index) = self.dense.get(&node) {
+            return index;
+        }
+        let index = self.external.len() as u32;
+        self.dense.insert(node, index);
+        self.external.push(node);
+        index
+    }
+
+    // Dense index of a node, None if it was never interned.
+    pub fn get(&self, node: usize) -> Option<u32> {
+        self.dense.get(&node).copied()
+    }
+
+    // External id of a dense index.
+    pub fn external(&self, index: u32) -> usize {
+        self.external[index as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.external.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.external.is_empty()
+    }
+}
+
+// Compressed sparse row graph with dense node ids.
+// The out edges of node i are out_targets[out_offsets[i]..out_offsets[i + 1]]
+// (same positions in out_weights / out_timestamps), and the in edges are
+// stored the same way in the in_* arrays, with in_positions pointing at the
+// edge in the out_* arrays. The source node is implied by the row, so this
+// uses much less memory than Graph for large networks.
+#[derive(Debug, Clone)]
+pub struct CsrGraph {
+    pub interner: NodeInterner,
+    out_offsets: Vec<usize>,
+    out_targets: Vec<u32>,
+    out_weights: Vec<f64>,
+    out_timestamps: Vec<Option<i64>>,
+    in_offsets: Vec<usize>,
+    in_sources: Vec<u32>,
+    in_positions: Vec<usize>,
+}
+
+impl CsrGraph {
+    // Constructor from a list of edges. Edges keep their order within a row.
+    pub fn from_edges(edge_lst: &[Edge]) -> CsrGraph {
+        let mut interner = NodeInterner::new();
+        let mut dense_edges: Vec<(u32, u32)> = Vec::with_capacity(edge_lst.len());
+        for edge in edge_lst {
+            let from = interner.intern(edge.from);
+            let to = interner.intern(edge.to);
+            dense_edges.push((from, to));
+        }
+
+        let num_nodes = interner.len();
+        let mut out_offsets = vec![0; num_nodes + 1];
+        let mut in_offsets = vec![0; num_nodes + 1];
+        for &(from, to) in &dense_edges {
+            out_offsets[from as usize + 1] += 1;
+            in_offsets[to as usize + 1] += 1;
+        }
+        for i in 0..num_nodes {
+            out_offsets[i + 1] += out_offsets[i];
+            in_offsets[i + 1] += in_offsets[i];
+        }
+
+        // Fill the rows, using a cursor per row
+        let mut out_targets = vec![0; edge_lst.len()];
+        let mut out_weights = vec![0.0; edge_lst.len()];
+        let mut out_timestamps = vec![None; edge_lst.len()];
+        let mut in_sources = vec![0; edge_lst.len()];
+        let mut in_positions = vec![0; edge_lst.len()];
+        let mut out_cursor = out_offsets.clone();
+        let mut in_cursor = in_offsets.clone();
+
+        for (edge, &(from, to)) in edge_lst.iter().zip(dense_edges.iter()) {
+            let out_position = out_cursor[from as usize];
+            out_targets[out_position] = to;
+            out_weights[out_position] = edge.weight;
+            out_timestamps[out_position] = edge.timestamp;
+            out_cursor[from as usize] += 1;
+
+            let in_position = in_cursor[to as usize];
+            in_sources[in_position] = from;
+            in_positions[in_position] = out_position;
+            in_cursor[to as usize] += 1;
+        }
+
+        CsrGraph { interner, out_offsets, out_targets, out_weights, out_timestamps, in_offsets, in_sources, in_positions }
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.interner.len()
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.out_targets.len()
+    }
+
+    // Dense targets of the out edges of a dense node.
+    pub fn out_neighbors(&self, index: u32) -> &[u32] {
+        &self.out_targets[self.out_range(index)]
+    }
+
+    // Dense sources of the in edges of a dense node.
+    pub fn in_neighbors(&self, index: u32) -> &[u32] {
+        &self.in_sources[self.in_range(index)]
+    }
+
+    fn out_range(&self, index: u32) -> Range<usize> {
+        self.out_offsets[index as usize]..self.out_offsets[index as usize + 1]
+    }
+
+    fn in_range(&self, index: u32) -> Range<usize> {
+        self.in_offsets[index as usize]..self.in_offsets[index as usize + 1]
+    }
+
+    // helper for the Network methods: the rows of a node by external id,
+    // empty if the node isn't in the graph.
+    fn rows(&self, node: usize) -> (Range<usize>, Range<usize>) {
+        match self.interner.get(node) {
+            Some(index) => (self.out_range(index), self.in_range(index)),
+            None => (0..0, 0..0),
+        }
+    }
+
+    // The edge at a position of the out_* arrays, from is its source.
+    fn edge_at(&self, from: usize, position: usize) -> Edge {
+        Edge {
+            from,
+            to: self.interner.external(self.out_targets[position]),
+            weight: self.out_weights[position],
+            timestamp: self.out_timestamps[position],
+        }
+    }
+}
+
+impl Network for CsrGraph {
+    fn nodes(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.num_nodes() as u32).map(|index| self.interner.external(index))
+    }
+
+    fn node_count(&self) -> usize {
+        self.num_nodes()
+    }
+
+    fn contains(&self, node: usize) -> bool {
+        self.interner.get(node).is_some()
+    }
+
+    fn out_edges(&self, node: usize) -> impl Iterator<Item = Edge> + '_ {
+        self.rows(node).0.map(move |position| self.edge_at(node, position))
+    }
+
+    fn in_edges(&self, node: usize) -> impl Iterator<Item = Edge> + '_ {
+        self.rows(node).1.map(|position| {
+            let from = self.interner.external(self.in_sources[position]);
+            self.edge_at(from, self.in_positions[position])
+        })
+    }
+
+    fn out_degree(&self, node: usize) -> usize {
+        self.rows(node).0.len()
+    }
+
+    fn in_degree(&self, node: usize) -> usize {
+        self.rows(node).1.len()
+    }
+}
+
+impl From<&Graph> for CsrGraph {
+    fn from(graph: &Graph) -> CsrGraph {
+        let edges: Vec<Edge> = graph.edges().copied().collect();
+        let mut csr = CsrGraph::from_edges(&edges);
+
+        // Nodes without any edge can't come from the edge list, add them as empty rows.
+        for node in graph.nodes() {
+            if csr.interner.get(node).is_none() {
+                csr.interner.intern(node);
+                csr.out_offsets.push(csr.num_edges());
+                csr.in_offsets.push(csr.num_edges());
+            }
+        }
+        csr
+    }
+}
+
+// ----------------------- TESTS -----------------------
+
+#[cfg(test)]
+fn test_edges() -> Vec<Edge> {
+    vec![
+        Edge { from: 10, to: 20, weight: 1.0, timestamp: Some(5) },
+        Edge { from: 20, to: 30, weight: 2.0, timestamp: None },
+        Edge { from: 30, to: 10, weight: -4.0, timestamp: None },
+        Edge { from: 40, to: 20, weight: 3.0, timestamp: None },
+        Edge { from: 50, to: 60, weight: 1.0, timestamp: None },
+    ]
+}
+
+#[test]
+fn test_node_interner() {
+    let mut interner = NodeInterner::new();
+    assert_eq!(interner.intern(7188), 0);
+    assert_eq!(interner.intern(430), 1);
+    assert_eq!(interner.intern(7188), 0); // Same id, same index
+
+    assert_eq!(interner.get(430), Some(1));
+    assert_eq!(interner.get(1), None);
+    assert_eq!(interner.external(1), 430);
+    assert_eq!(interner.len(), 2);
+}
+
+#[test]
+fn test_csr_rows() {
+    let csr = CsrGraph::from_edges(&test_edges());
+    let node_20 = csr.interner.get(20).unwrap();
+
+    assert_eq!(csr.num_nodes(), 6);
+    assert_eq!(csr.num_edges(), 5);
+    assert_eq!(csr.out_neighbors(node_20), &[csr.interner.get(30).unwrap()]);
+    assert_eq!(csr.in_neighbors(node_20).len(), 2);
+    assert_eq!(csr.in_edges(20).map(|edge| edge.weight).sum::<f64>(), 4.0);
+}
+
+#[test]
+fn test_csr_matches_graph() {
+    let edges = test_edges();
+    let graph = Graph::new(&edges);
+    let csr = CsrGraph::from(&graph);
+
+    assert_eq!(csr.get_degrees(), graph.get_degrees());
+    for node in graph.nodes() {
+        assert_eq!(csr.clustering_coefficient(node), graph.clustering_coefficient(node));
+        assert_eq!(csr.get_trust_score(node), graph.get_trust_score(node));
+    }
+    assert_eq!(csr.find_subgraphs().len(), graph.find_subgraphs().len());
+}
+
+#[test]
+fn test_csr_edges() {
+    let csr = CsrGraph::from_edges(&test_edges());
+
+    let out_edge = csr.out_edges(10).next().unwrap();
+    assert_eq!((out_edge.to, out_edge.timestamp), (20, Some(5))); // Timestamps are kept
+    let in_edge = csr.in_edges(10).next().unwrap();
+    assert_eq!((in_edge.from, in_edge.weight), (30, -4.0));
+    assert_eq!(csr.in_degree(20), 2);
+    assert_eq!(csr.out_edges(99).count(), 0); // Unknown node
+}
@@ -2,16 +2,35 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 
 pub mod algorithm;
-use algorithm::bfs;
-// use algorithm::dijkstra;
+pub mod balance;
+pub mod community;
+pub mod csr;
+pub mod reputation;
+use algorithm::shortest_path;
+use algorithm::strongly_connected_components;
+use algorithm::triangle_stats;
+use algorithm::weak_components;
+use algorithm::WeakComponents;
 
 // Edge struct used to represent a specific edge in the graph
-// the forth field - TimeStamp is removed since not using.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub struct Edge {
-    pub from: usize,       // Source node that this edge is from
-    pub to: usize,         // Node that this edge points to
-    pub weight: f64,       // Weight of the edge.
+    pub from: usize,                // Source node that this edge is from
+    pub to: usize,                  // Node that this edge points to
+    pub weight: f64,                // Weight of the edge.
+    pub timestamp: Option<i64>,     // Time of the rating in seconds since epoch, if known.
+}
+
+// Which clustering coefficient Network::clustering_coefficients computes.
+// The weighted ones use the undirected graph where two nodes are linked if
+// either rated the other, with the mean absolute rating between them as
+// the link weight and the sign of their summed ratings as its sign.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClusteringVariant {
+    Unweighted,     // clustering_coefficient, ratings between neighbours over possible ones
+    Onnela,         // Geometric mean of the triangle weights over the largest weight (Onnela et al. 2005)
+    Barrat,         // Triangles weighted by the node's two links in them (Barrat et al. 2004)
+    Signed,         // Balanced minus unbalanced triangles over neighbour pairs (Kunegis et al. 2009), in [-1, 1]
 }
 
 #[derive(Debug, Clone)]
@@ -23,23 +42,184 @@ pub struct NodeNeighbors {
 // Grpah struct - corresponds to a complete graph
 // represents by a hashtable with the node as the key 
 // and the Edge struct containing the information of
+// its outgoing edges. `incoming` lists, for every node, where the
+// edges pointing to it are stored in `content` as (from, position),
+// so in-neighbour lookups don't need to scan the whole graph.
+// Both are private so they can't get out of sync.
 #[derive(Debug, Clone)]
-pub struct Graph { pub content: HashMap<usize, Vec<Edge>> }
+pub struct Graph {
+    content: HashMap<usize, Vec<Edge>>,
+    incoming: HashMap<usize, Vec<(usize, usize)>>,
+}
 
 impl Graph {
     // Constructor that transform a list of Edge struct to Graph
-    pub fn new(edge_lst: &Vec<Edge>) -> Graph {
+    pub fn new(edge_lst: &[Edge]) -> Graph {
         let mut graph_hashmap: HashMap<usize, Vec<Edge>> = HashMap::new();
 
         for edge in edge_lst.iter() {
             // Add edge to the source node's adjacency list
-            graph_hashmap.entry(edge.from).or_insert(Vec::new()).push(edge.clone());
+            graph_hashmap.entry(edge.from).or_default().push(*edge);
             
             // Ensure the target node is also included in the graph, even if it has no outgoing edges
-            graph_hashmap.entry(edge.to).or_insert(Vec::new());
+            graph_hashmap.entry(edge.to).or_default();
         }
 
-        Graph { content: graph_hashmap }
+        Graph::from_content(graph_hashmap)
+    }
+
+    // Constructor from an adjacency map (node -> outgoing edges),
+    // builds the incoming edge index. Targets without a row of their
+    // own get an empty one, same as in Graph::new.
+    pub fn from_content(mut content: HashMap<usize, Vec<Edge>>) -> Graph {
+        let mut incoming: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+
+        for (&from, edges) in content.iter() {
+            for (position, edge) in edges.iter().enumerate() {
+                incoming.entry(edge.to).or_default().push((from, position));
+            }
+        }
+        for &node in incoming.keys() {
+            content.entry(node).or_default();
+        }
+
+        Graph { content, incoming }
+    }
+
+    // The adjacency map (node -> outgoing edges), read only so the
+    // incoming edge index stays in sync.
+    pub fn content(&self) -> &HashMap<usize, Vec<Edge>> {
+        &self.content
+    }
+
+    // All nodes of the graph, in no particular order.
+    pub fn nodes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.content.keys().copied()
+    }
+
+    // Number of nodes in the graph.
+    pub fn node_count(&self) -> usize {
+        self.content.len()
+    }
+
+    // Whether node is in the graph.
+    pub fn contains(&self, node: usize) -> bool {
+        self.content.contains_key(&node)
+    }
+
+    // All edges of the graph, grouped by the node they come from.
+    pub fn edges(&self) -> impl Iterator<Item = &Edge> + '_ {
+        self.content.values().flatten()
+    }
+
+    // Edges going out of a node (empty if the node isn't in the graph).
+    pub fn out_edges(&self, node: usize) -> &[Edge] {
+        self.content.get(&node).map(|edges| edges.as_slice()).unwrap_or(&[])
+    }
+
+    // Edges pointing to a node (empty if nobody rated it).
+    pub fn in_edges(&self, node: usize) -> impl Iterator<Item = &Edge> + '_ {
+        self.incoming.get(&node).into_iter().flatten().map(|&(from, position)| &self.out_edges(from)[position])
+    }
+
+    // Number of edges going out of a node.
+    pub fn out_degree(&self, node: usize) -> usize {
+        self.out_edges(node).len()
+    }
+
+    // Number of edges pointing to a node.
+    pub fn in_degree(&self, node: usize) -> usize {
+        self.incoming.get(&node).map(|positions| positions.len()).unwrap_or(0)
+    }
+
+    // The analyses below are the Network ones, kept here so callers
+    // don't need the trait in scope.
+
+    // helper method that get the list of neighbors of a node.
+    pub fn get_neighbors(&self, node: usize) -> NodeNeighbors {
+        Network::get_neighbors(self, node)
+    }
+
+    // Nodes connected to node by a rating in either direction (no duplicates).
+    pub fn neighbor_set(&self, node: usize) -> HashSet<usize> {
+        Network::neighbor_set(self, node)
+    }
+
+    // compute the indegree and outdegree of ALL nodes in the graph.
+    // return value: (indegree HashMap, outdegre HashMap)
+    pub fn get_degrees(&self) -> (HashMap<usize, f64>, HashMap<usize, f64>) {
+        Network::get_degrees(self)
+    }
+
+    // Clustering coefficient of a given node, see Network::clustering_coefficient.
+    pub fn clustering_coefficient(&self, node: usize) -> f64 {
+        Network::clustering_coefficient(self, node)
+    }
+
+    // Clustering coefficient of every node with the given variant.
+    pub fn clustering_coefficients(&self, variant: ClusteringVariant) -> HashMap<usize, f64> {
+        Network::clustering_coefficients(self, variant)
+    }
+
+    // Finds all connected subgraphs in the graph (edge direction ignored).
+    pub fn find_subgraphs(&self) -> Vec<Graph> {
+        Network::find_subgraphs(self)
+    }
+
+    // Same as find_subgraphs for weak components that were already computed.
+    pub fn component_subgraphs(&self, components: &WeakComponents) -> Vec<Graph> {
+        Network::component_subgraphs(self, components)
+    }
+
+    // Finds all strongly connected subgraphs in the graph, following edge direction.
+    pub fn find_strong_subgraphs(&self) -> Vec<Graph> {
+        Network::find_strong_subgraphs(self)
+    }
+
+    // Cheapest path from one node to another through positive ratings.
+    pub fn shortest_path(&self, from: usize, to: usize) -> Option<(f64, Vec<usize>)> {
+        Network::shortest_path(self, from, to)
+    }
+
+    // Mean weight of the ratings a node received.
+    pub fn get_trust_score(&self, node: usize) -> f64 {
+        Network::get_trust_score(self, node)
+    }
+
+    // Earliest and latest timestamp over all edges that carry one.
+    pub fn time_range(&self) -> Option<(i64, i64)> {
+        Network::time_range(self)
+    }
+}
+
+// Read access to a graph. Graph and csr::CsrGraph both implement it, so the
+// algorithms and GraphInfo run on either. Edges are handed out by value
+// because CsrGraph doesn't store Edge structs.
+pub trait Network: Sized {
+    // All nodes of the graph, in no particular order.
+    fn nodes(&self) -> impl Iterator<Item = usize> + '_;
+
+    // Number of nodes in the graph.
+    fn node_count(&self) -> usize;
+
+    // Whether node is in the graph.
+    fn contains(&self, node: usize) -> bool;
+
+    // Edges going out of a node (empty if the node isn't in the graph).
+    fn out_edges(&self, node: usize) -> impl Iterator<Item = Edge> + '_;
+
+    // Edges pointing to a node (empty if nobody rated it).
+    fn in_edges(&self, node: usize) -> impl Iterator<Item = Edge> + '_;
+
+    // Number of edges going out of a node.
+    fn out_degree(&self, node: usize) -> usize;
+
+    // Number of edges pointing to a node.
+    fn in_degree(&self, node: usize) -> usize;
+
+    // All edges of the graph, grouped by the node they come from.
+    fn edges(&self) -> impl Iterator<Item = Edge> + '_ {
+        self.nodes().flat_map(move |node| self.out_edges(node))
     }
 
     // helper method that get the list of neighbors of a node.
@@ -47,64 +227,59 @@ impl Graph {
         let mut input_nodes: Vec<usize> = Vec::new();
         let mut output_nodes: Vec<usize> = Vec::new();
 
-        if let Some(edges) = self.content.get(&node) {
-            for edge in edges {
-                output_nodes.push(edge.to);
-            }
+        for edge in self.out_edges(node) {
+            output_nodes.push(edge.to);
         }
 
-        for (source_node, edges) in &self.content {
-            for edge in edges {
-                if edge.to == node {
-                    input_nodes.push(*source_node);
-                }
-            }
+        for edge in self.in_edges(node) {
+            input_nodes.push(edge.from);
         }
 
-        return NodeNeighbors{ input_nodes: input_nodes, output_nodes: output_nodes };
+        NodeNeighbors{ input_nodes, output_nodes }
     }
 
 
+    // Nodes connected to node by a rating in either direction (no duplicates).
+    fn neighbor_set(&self, node: usize) -> HashSet<usize> {
+        let neighbors = self.get_neighbors(node);
+
+        let mut nb_set: HashSet<usize> = HashSet::new();
+        for nb in neighbors.output_nodes.iter() {
+            nb_set.insert(*nb);
+        }
+        for nb in neighbors.input_nodes.iter() {
+            nb_set.insert(*nb);
+        }
+        nb_set
+    }
+
     // compute the indegree and outdegree of ALL nodes in the graph.
     // return value: (indegree HashMap, outdegre HashMap)
-    pub fn get_degrees(&self) -> (HashMap<usize, f64>, HashMap<usize, f64>) {
+    fn get_degrees(&self) -> (HashMap<usize, f64>, HashMap<usize, f64>) {
         let mut in_degree = HashMap::new();
         let mut out_degree = HashMap::new();
 
-        for node in self.content.keys() {
-            let neighbors = self.get_neighbors(*node);
+        for node in self.nodes() {
+            out_degree.insert(node, self.out_degree(node) as f64);
 
-            out_degree.insert(*node, neighbors.output_nodes.len() as f64);
-
-            in_degree.insert(*node, neighbors.input_nodes.len() as f64);
+            in_degree.insert(node, self.in_degree(node) as f64);
         }
-        return (in_degree, out_degree);
+        (in_degree, out_degree)
     }
 
     // This function calculates the clustering coefficient of a given node.
     // Clustering coefficient "is a measure of the degree to which nodes 
     // in a graph tend to cluster together." - Wikipedia.
     // Its formula is cc(n) = 2 * total edges between neighbors of n / # neighbor * (# neighbor - 1)
-    pub fn clustering_coefficient(&self, node: usize) -> f64 {
-        let neighbors = self.get_neighbors(node);
-
+    fn clustering_coefficient(&self, node: usize) -> f64 {
         let mut edges_btw_nb = 0;
-        let mut nb_set: HashSet<usize> = HashSet::new();
-
-        for nb in neighbors.output_nodes.iter() {
-            nb_set.insert(nb.clone());
-        }
-        for nb in neighbors.input_nodes.iter() {
-            nb_set.insert(nb.clone());
-        }
+        let nb_set: HashSet<usize> = self.neighbor_set(node);
 
         // total edges between neighbors of n
         for &neighbor in &nb_set {
-            if let Some(edges) = self.content.get(&neighbor) {
-                for edge in edges {
-                    if nb_set.contains(&edge.to) {
-                        edges_btw_nb += 1; // Count only directed edges between neighbors
-                    }
+            for edge in self.out_edges(neighbor) {
+                if nb_set.contains(&edge.to) {
+                    edges_btw_nb += 1; // Count only directed edges between neighbors
                 }
             }
         }
@@ -116,58 +291,210 @@ impl Graph {
 
         let possible_connections = num_nb * (num_nb - 1);
 
-        return edges_btw_nb as f64 / possible_connections as f64
+        edges_btw_nb as f64 / possible_connections as f64
     }
 
-    // Finds all connected subgraphs in the graph
-    pub fn find_subgraphs(&self) -> Vec<Graph> {
-        let mut visited = HashSet::new();
+    // Clustering coefficient of every node with the given variant.
+    fn clustering_coefficients(&self, variant: ClusteringVariant) -> HashMap<usize, f64> {
+        if variant == ClusteringVariant::Unweighted {
+            return triangle_stats(self).rating_clustering;
+        }
+
+        let max_weight = self.edges().map(|edge| edge.weight.abs()).fold(0.0, f64::max);
+        let max_weight = if max_weight > 0.0 { max_weight } else { 1.0 };
+        // Links of every node once, including nodes that are only rated (a sub graph can keep edges leaving it)
+        let mut links: HashMap<usize, HashMap<usize, (f64, f64)>> = HashMap::new();
+        for node in self.nodes().chain(self.edges().map(|edge| edge.to)) {
+            links.entry(node).or_insert_with(|| undirected_links(self, node));
+        }
+        self.nodes().map(|node| (node, weighted_clustering(&links, node, variant, max_weight))).collect()
+    }
+
+    // Finds all connected subgraphs in the graph (edge direction ignored).
+    // Subgraph i is component i of weak_components, so
+    // weak_components().component_of(node) is the index of the node's subgraph.
+    fn find_subgraphs(&self) -> Vec<Graph> {
+        self.component_subgraphs(&weak_components(self))
+    }
+
+    // Same as find_subgraphs for weak components that were already computed.
+    fn component_subgraphs(&self, components: &WeakComponents) -> Vec<Graph> {
         let mut subgraphs = Vec::new();
 
-        for &node in self.content.keys() {
-            if !visited.contains(&node) {
-                // Use BFS to find all nodes in the connected subgraph
-                let subgraph_nodes = bfs(&self, node, &mut visited);
-
-                // Construct the subgraph from the collected nodes
-                let mut subgraph_content = HashMap::new();
-                for subgraph_node in subgraph_nodes.iter() {
-                    if let Some(edges) = self.content.get(subgraph_node) {
-                        subgraph_content.insert(*subgraph_node, edges.clone());
-                    }
+        for component in 0..components.len() {
+            // Construct the subgraph from the nodes of the component
+            let mut subgraph_content = HashMap::new();
+            for &subgraph_node in components.members(component) {
+                if self.contains(subgraph_node) {
+                    subgraph_content.insert(subgraph_node, self.out_edges(subgraph_node).collect());
                 }
+            }
+
+            subgraphs.push(Graph::from_content(subgraph_content));
+        }
+
+        subgraphs
+    }
+
+    // Finds all strongly connected subgraphs in the graph, following edge direction.
+    // Each subgraph only keeps the edges between its own nodes.
+    fn find_strong_subgraphs(&self) -> Vec<Graph> {
+        let mut subgraphs = Vec::new();
 
-                subgraphs.push(Graph { content: subgraph_content });
+        for component in strongly_connected_components(self) {
+            let mut subgraph_content = HashMap::new();
+            for &node in component.iter() {
+                let inner_edges: Vec<Edge> = self.out_edges(node)
+                    .filter(|edge| component.contains(&edge.to))
+                    .collect();
+                subgraph_content.insert(node, inner_edges);
             }
+            subgraphs.push(Graph::from_content(subgraph_content));
         }
 
-        return subgraphs;
+        subgraphs
+    }
+
+    // Cheapest path from one node to another through positive ratings,
+    // see algorithm::trust_distance for the cost of a rating.
+    // Returns (total cost, nodes on the path) or None if there is no such path.
+    fn shortest_path(&self, from: usize, to: usize) -> Option<(f64, Vec<usize>)> {
+        shortest_path(self, from, to)
     }
 
     // Calculate the trust score of a given node
     // this is determining by the sum of the
     // weight of the indegree edges divided by
     // the number of total indegrees.
-    pub fn get_trust_score(&self, node: usize) -> f64 {
+    fn get_trust_score(&self, node: usize) -> f64 {
         let mut total_trust_score = 0.0;
         let mut node_count = 0;
 
-        for (_, edges) in &self.content {
-            for edge in edges {
-                if edge.to == node {
-                    total_trust_score += edge.weight;
-                    node_count += 1;
-                }
-            }
+        for edge in self.in_edges(node) {
+            total_trust_score += edge.weight;
+            node_count += 1;
         }
 
         if node_count == 0 {
             return 0.0;
         }
 
-        return total_trust_score as f64 / node_count as f64
+        total_trust_score / node_count as f64
+    }
+
+    // Earliest and latest timestamp over all edges that carry one.
+    // Returns None if no edge in the graph has a timestamp.
+    fn time_range(&self) -> Option<(i64, i64)> {
+        let mut range: Option<(i64, i64)> = None;
+
+        for edge in self.edges() {
+            if let Some(time) = edge.timestamp {
+                range = match range {
+                    Some((start, end)) => Some((start.min(time), end.max(time))),
+                    None => Some((time, time)),
+                };
+            }
+        }
+
+        range
+    }
+}
+
+impl Network for Graph {
+    fn nodes(&self) -> impl Iterator<Item = usize> + '_ {
+        Graph::nodes(self)
+    }
+
+    fn node_count(&self) -> usize {
+        Graph::node_count(self)
+    }
+
+    fn contains(&self, node: usize) -> bool {
+        Graph::contains(self, node)
+    }
+
+    fn out_edges(&self, node: usize) -> impl Iterator<Item = Edge> + '_ {
+        Graph::out_edges(self, node).iter().copied()
+    }
+
+    fn in_edges(&self, node: usize) -> impl Iterator<Item = Edge> + '_ {
+        Graph::in_edges(self, node).copied()
+    }
+
+    fn out_degree(&self, node: usize) -> usize {
+        Graph::out_degree(self, node)
+    }
+
+    fn in_degree(&self, node: usize) -> usize {
+        Graph::in_degree(self, node)
+    }
+
+    fn edges(&self) -> impl Iterator<Item = Edge> + '_ {
+        Graph::edges(self).copied()
+    }
+}
+
+// Weighted or signed clustering coefficient of node, see ClusteringVariant.
+// all_links has the undirected_links of every node.
+fn weighted_clustering(all_links: &HashMap<usize, HashMap<usize, (f64, f64)>>, node: usize,
+                       variant: ClusteringVariant, max_weight: f64) -> f64 {
+    let links = &all_links[&node];
+    let num_nb = links.len();
+    if num_nb < 2 {
+        return 0.0;
+    }
+
+    // Every triangle node, j, h once (j < h)
+    let mut total = 0.0;
+    for (&j, &(w_ij, sign_ij)) in links {
+        for (&h, &(w_jh, sign_jh)) in &all_links[&j] {
+            if h <= j {
+                continue;
+            }
+            if let Some(&(w_ih, sign_ih)) = links.get(&h) {
+                total += match variant {
+                    ClusteringVariant::Onnela => (w_ij * w_ih * w_jh).cbrt() / max_weight,
+                    ClusteringVariant::Barrat => w_ij + w_ih,
+                    ClusteringVariant::Signed => sign_ij * sign_ih * sign_jh,
+                    ClusteringVariant::Unweighted => 1.0,
+                };
+            }
+        }
+    }
+
+    if variant == ClusteringVariant::Barrat {
+        let strength: f64 = links.values().map(|(weight, _)| weight).sum();
+        if strength == 0.0 {
+            return 0.0;
+        }
+        total / (strength * (num_nb - 1) as f64)
+    } else {
+        2.0 * total / (num_nb * (num_nb - 1)) as f64
+    }
+}
+
+// Neighbours of node in the undirected graph with the (weight, sign) of the
+// link: the mean absolute rating between them, and 1, -1 or 0 for the sign
+// of their summed ratings. Self ratings are left out.
+fn undirected_links<G: Network>(graph: &G, node: usize) -> HashMap<usize, (f64, f64)> {
+    let mut totals: HashMap<usize, (f64, f64, usize)> = HashMap::new();
+    let out_links = graph.out_edges(node).map(|edge| (edge.to, edge.weight));
+    let in_links = graph.in_edges(node).map(|edge| (edge.from, edge.weight));
+    for (neighbor, weight) in out_links.chain(in_links) {
+        if neighbor != node {
+            let total = totals.entry(neighbor).or_insert((0.0, 0.0, 0));
+            total.0 += weight;
+            total.1 += weight.abs();
+            total.2 += 1;
+        }
     }
 
+    totals.into_iter()
+        .map(|(neighbor, (sum, abs_sum, count))| {
+            let sign = if sum > 0.0 { 1.0 } else if sum < 0.0 { -1.0 } else { 0.0 };
+            (neighbor, (abs_sum / count as f64, sign))
+        })
+        .collect()
 }
 
 // ----------------------- TESTS ----------------------- 
@@ -175,45 +502,45 @@ impl Graph {
 #[test]
 fn test_graph_new_with_edges() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
-        Edge { from: 2, to: 3, weight: 2.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 2.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
 
-    assert_eq!(graph.content.len(), 3); // Nodes 1, 2, 3
-    assert!(graph.content.contains_key(&1));
-    assert!(graph.content.contains_key(&2));
-    assert!(graph.content.contains_key(&3));
+    assert_eq!(graph.node_count(), 3); // Nodes 1, 2, 3
+    assert!(graph.contains(1));
+    assert!(graph.contains(2));
+    assert!(graph.contains(3));
 }
 
 #[test]
 fn test_graph_include_target_nodes() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
 
-    assert!(graph.content.contains_key(&2)); // Ensure target node 2 is included
-    assert_eq!(graph.content[&2].len(), 0); // Node 2 has no outgoing edges
+    assert!(graph.contains(2)); // Ensure target node 2 is included
+    assert_eq!(graph.out_degree(2), 0); // Node 2 has no outgoing edges
 }
 
 #[test]
 fn test_get_neighbors() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
-        Edge { from: 3, to: 2, weight: 1.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 2, weight: 1.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
 
     let neighbors = graph.get_neighbors(2);
-    assert_eq!(neighbors.input_nodes.contains(&1) || neighbors.input_nodes.contains(&3), true);
+    assert!(neighbors.input_nodes.contains(&1) || neighbors.input_nodes.contains(&3));
     assert_eq!(neighbors.output_nodes, vec![]);
 }
 
 #[test]
 fn test_get_neighbors_no_edges() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
 
@@ -225,8 +552,8 @@ fn test_get_neighbors_no_edges() {
 #[test]
 fn test_get_degrees() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
-        Edge { from: 2, to: 3, weight: 1.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
 
@@ -238,7 +565,7 @@ fn test_get_degrees() {
 #[test]
 fn test_get_degrees_no_outgoing_or_incoming() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
 
@@ -251,9 +578,9 @@ fn test_get_degrees_no_outgoing_or_incoming() {
 #[test]
 fn test_clustering_coefficient_connected() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
-        Edge { from: 2, to: 3, weight: 1.0 },
-        Edge { from: 3, to: 1, weight: 1.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 1.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
 
@@ -267,7 +594,7 @@ fn test_clustering_coefficient_connected() {
 #[test]
 fn test_clustering_coefficient_no_neighbors() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
 
@@ -275,12 +602,57 @@ fn test_clustering_coefficient_no_neighbors() {
     assert_eq!(cc, 0.0); // Node 3 has no neighbors
 }
 
+#[test]
+fn test_weighted_clustering_variants() {
+    // 1 rates 2 (10), 3 (5) and 4 (1), and 2 and 3 rate each other (8 and 4)
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 10.0, timestamp: None },
+        Edge { from: 1, to: 3, weight: 5.0, timestamp: None },
+        Edge { from: 1, to: 4, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 8.0, timestamp: None },
+        Edge { from: 3, to: 2, weight: 4.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    let onnela = graph.clustering_coefficients(ClusteringVariant::Onnela);
+    assert!((onnela[&1] - (10.0f64 * 5.0 * 6.0).cbrt() / 10.0 / 3.0).abs() < 1e-12); // 2 - 3 weighs (8 + 4) / 2
+    let barrat = graph.clustering_coefficients(ClusteringVariant::Barrat);
+    assert!((barrat[&1] - 15.0 / (16.0 * 2.0)).abs() < 1e-12);
+    let signed = graph.clustering_coefficients(ClusteringVariant::Signed);
+    assert!((signed[&1] - 1.0 / 3.0).abs() < 1e-12);
+    assert_eq!(signed[&4], 0.0);
+
+    let unweighted = graph.clustering_coefficients(ClusteringVariant::Unweighted);
+    assert_eq!(unweighted[&1], graph.clustering_coefficient(1));
+}
+
+#[test]
+fn test_signed_clustering() {
+    // Same triangle with all positive or one negative rating
+    let positive = vec![
+        Edge { from: 1, to: 2, weight: 10.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 10.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 10.0, timestamp: None },
+    ];
+    let mut negative = positive.clone();
+    negative[2].weight = -10.0;
+
+    let balanced = Graph::new(&positive).clustering_coefficients(ClusteringVariant::Signed);
+    let unbalanced = Graph::new(&negative).clustering_coefficients(ClusteringVariant::Signed);
+    assert_eq!(balanced[&1], 1.0);
+    assert_eq!(unbalanced[&1], -1.0);
+
+    // The weighted variants only see the absolute weights
+    let onnela = Graph::new(&negative).clustering_coefficients(ClusteringVariant::Onnela);
+    assert!((onnela[&1] - 1.0).abs() < 1e-12);
+}
+
 // Entire graph is connected
 #[test]
 fn test_find_one_subgraphs() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
-        Edge { from: 2, to: 3, weight: 1.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
 
@@ -291,8 +663,8 @@ fn test_find_one_subgraphs() {
 #[test]
 fn test_find_subgraphs() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.0 },
-        Edge { from: 3, to: 4, weight: 1.0 },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 1.0, timestamp: None },
     ];
     let graph = Graph::new(&edges);
 
@@ -303,8 +675,8 @@ fn test_find_subgraphs() {
 #[test]
 fn test_get_trust_score() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.5 },
-        Edge { from: 3, to: 2, weight: 2.5 },
+        Edge { from: 1, to: 2, weight: 1.5, timestamp: None },
+        Edge { from: 3, to: 2, weight: 2.5, timestamp: None },
     ];
     let graph = Graph::new(&edges);
 
@@ -315,10 +687,94 @@ fn test_get_trust_score() {
 #[test]
 fn test_get_trust_score_zero_indegree() {
     let edges = vec![
-        Edge { from: 1, to: 2, weight: 1.5 },
+        Edge { from: 1, to: 2, weight: 1.5, timestamp: None },
     ];
     let graph = Graph::new(&edges);
 
     let trust_score = graph.get_trust_score(3);
     assert_eq!(trust_score, 0.0); // No incoming edges for node 3
 }
+
+#[test]
+fn test_time_range() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: Some(300) },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: Some(100) },
+        Edge { from: 3, to: 1, weight: 1.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    assert_eq!(graph.time_range(), Some((100, 300)));
+}
+
+#[test]
+fn test_incoming_index() {
+    let edges = vec![
+        Edge { from: 1, to: 3, weight: 2.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: -1.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 1.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    assert_eq!(graph.in_degree(3), 2);
+    assert_eq!(graph.in_degree(2), 0);
+    assert_eq!(graph.in_edges(1).next().unwrap().from, 3);
+    assert_eq!(graph.in_edges(3).map(|edge| edge.weight).sum::<f64>(), 1.0);
+    assert_eq!(graph.out_degree(3), 1);
+    assert_eq!(graph.out_edges(42).len(), 0); // Unknown node
+
+    // Sub graphs get their own index
+    let subgraphs = graph.find_subgraphs();
+    assert_eq!(subgraphs[0].in_degree(3), 2);
+}
+
+#[test]
+fn test_find_strong_subgraphs() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 1, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    let mut subgraphs = graph.find_strong_subgraphs();
+    subgraphs.sort_by_key(|subgraph| subgraph.node_count());
+    assert_eq!(subgraphs.len(), 2); // {1, 2} and {3}
+    assert_eq!(subgraphs[1].node_count(), 2);
+    assert_eq!(subgraphs[1].out_degree(2), 1); // Edge 2 -> 3 leaves the component
+    assert_eq!(subgraphs[0].out_degree(3), 0);
+}
+
+#[test]
+fn test_find_subgraphs_matches_components() {
+    let edges = vec![
+        Edge { from: 7, to: 8, weight: 1.0, timestamp: None },
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 2, weight: 1.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    let subgraphs = graph.find_subgraphs();
+    let components = weak_components(&graph);
+    for node in [1, 2, 3, 7, 8] {
+        let component = components.component_of(node).unwrap();
+        assert!(subgraphs[component].contains(node));
+    }
+    assert_eq!(subgraphs[0].node_count(), 3);
+    assert_eq!(subgraphs[0].in_degree(2), 2); // Edges are kept
+}
+
+#[test]
+fn test_from_content_adds_targets() {
+    // 2 is only a target, it has no row in the map
+    let mut content = HashMap::new();
+    content.insert(1, vec![Edge { from: 1, to: 2, weight: 1.0, timestamp: None }]);
+    let graph = Graph::from_content(content);
+
+    assert!(graph.contains(2));
+    assert_eq!(graph.node_count(), 2);
+    let closeness = algorithm::closeness_centrality(&graph, algorithm::Direction::Out);
+    assert_eq!(closeness[&1], 1.0);
+    assert_eq!(closeness[&2], 0.0);
+    assert!(graph.content()[&2].is_empty());
+}
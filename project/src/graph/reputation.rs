@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+
+use crate::graph::Network;
+#[cfg(test)]
+use crate::graph::{Edge, Graph};
+
+// Options for pagerank and personalized_pagerank.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageRankOptions {
+    pub damping: f64,           // Probability of following a rating instead of jumping
+    pub tolerance: f64,         // Stop when the L1 change of the scores is below this
+    pub max_iterations: usize,
+    pub weighted: bool,         // Follow ratings in proportion to their weight instead of uniformly
+}
+
+impl Default for PageRankOptions {
+    fn default() -> PageRankOptions {
+        PageRankOptions { damping: 0.85, tolerance: 1e-10, max_iterations: 100, weighted: false }
+    }
+}
+
+// PageRank over the positive ratings of the graph. Negative ratings are
+// ignored since they are not an endorsement. Nodes without positive out
+// ratings (dangling nodes) spread their score like a random jump.
+// Scores sum to 1.
+pub fn pagerank<G: Network>(graph: &G, options: &PageRankOptions) -> HashMap<usize, f64> {
+    personalized_pagerank(graph, &[], options)
+}
+
+// PageRank where every random jump goes back to one of the seed nodes,
+// i.e. how much the network trusts each node from the viewpoint of the seeds.
+// Seeds that are not in the graph are ignored; with no valid seed this is
+// the ordinary PageRank.
+pub fn personalized_pagerank<G: Network>(graph: &G, seeds: &[usize], options: &PageRankOptions) -> HashMap<usize, f64> {
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort();
+    let num_nodes = nodes.len();
+    if num_nodes == 0 {
+        return HashMap::new();
+    }
+    let index_of: HashMap<usize, usize> = nodes.iter().enumerate().map(|(index, &node)| (node, index)).collect();
+
+    // Teleport vector: uniform over the seeds, or over every node
+    let mut teleport = vec![0.0; num_nodes];
+    let valid_seeds: Vec<usize> = seeds.iter().filter_map(|seed| index_of.get(seed).copied()).collect();
+    if valid_seeds.is_empty() {
+        teleport.iter_mut().for_each(|value| *value = 1.0 / num_nodes as f64);
+    } else {
+        for &seed in &valid_seeds {
+            teleport[seed] += 1.0 / valid_seeds.len() as f64;
+        }
+    }
+
+    // Transition probabilities along positive ratings
+    let transitions: Vec<Vec<(usize, f64)>> = nodes.iter()
+        .map(|&node| {
+            let positive: Vec<(usize, f64)> = graph.out_edges(node)
+                .filter(|edge| edge.weight > 0.0)
+                .map(|edge| (index_of[&edge.to], if options.weighted { edge.weight } else { 1.0 }))
+                .collect();
+            let total: f64 = positive.iter().map(|(_, weight)| weight).sum();
+            positive.into_iter().map(|(target, weight)| (target, weight / total)).collect()
+        })
+        .collect();
+
+    let mut scores = teleport.clone();
+    for _ in 0..options.max_iterations {
+        let dangling_mass: f64 = (0..num_nodes)
+            .filter(|&index| transitions[index].is_empty())
+            .map(|index| scores[index])
+            .sum();
+
+        let mut next_scores: Vec<f64> = teleport.iter()
+            .map(|jump| (1.0 - options.damping + options.damping * dangling_mass) * jump)
+            .collect();
+        for (index, targets) in transitions.iter().enumerate() {
+            for &(target, probability) in targets {
+                next_scores[target] += options.damping * scores[index] * probability;
+            }
+        }
+
+        let change: f64 = scores.iter().zip(next_scores.iter()).map(|(old, new)| (old - new).abs()).sum();
+        scores = next_scores;
+        if change < options.tolerance {
+            break;
+        }
+    }
+
+    nodes.into_iter().zip(scores).collect()
+}
+
+// Options for eigentrust.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EigenTrustOptions {
+    pub alpha: f64,             // Weight of the pre-trusted peers in every iteration
+    pub tolerance: f64,         // Stop when the L1 change of the scores is below this
+    pub max_iterations: usize,
+}
+
+impl Default for EigenTrustOptions {
+    fn default() -> EigenTrustOptions {
+        EigenTrustOptions { alpha: 0.1, tolerance: 1e-10, max_iterations: 100 }
+    }
+}
+
+// EigenTrust of Kamvar et al. (WWW 2003). Each rater's positive ratings are
+// normalized into local trust c(i, j) = w(i, j) / sum of w(i, k), then
+//   t = (1 - alpha) * C^T t + alpha * p
+// where p is uniform over the pre-trusted peers (or every node without any).
+// Raters with no positive rating trust p. This is the same walk as weighted
+// personalized PageRank with damping 1 - alpha, so it is computed with it.
+// Scores sum to 1.
+pub fn eigentrust<G: Network>(graph: &G, pre_trusted: &[usize], options: &EigenTrustOptions) -> HashMap<usize, f64> {
+    let walk = PageRankOptions {
+        damping: 1.0 - options.alpha,
+        tolerance: options.tolerance,
+        max_iterations: options.max_iterations,
+        weighted: true,
+    };
+    personalized_pagerank(graph, pre_trusted, &walk)
+}
+
+// Options for fairness_goodness.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FairnessGoodnessOptions {
+    pub tolerance: f64,         // Stop when no score changes by more than this
+    pub max_iterations: usize,
+}
+
+impl Default for FairnessGoodnessOptions {
+    fn default() -> FairnessGoodnessOptions {
+        FairnessGoodnessOptions { tolerance: 1e-6, max_iterations: 100 }
+    }
+}
+
+// How an iterative algorithm ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceReport {
+    pub iterations: usize,
+    pub converged: bool,        // false if max_iterations was hit first
+    pub final_change: f64,      // Largest score change in the last iteration
+}
+
+// Result of fairness_goodness.
+#[derive(Debug, Clone)]
+pub struct FairnessGoodness {
+    pub fairness: HashMap<usize, f64>,      // How reliable a node's ratings are, in [0, 1]
+    pub goodness: HashMap<usize, f64>,      // How much a node is trusted, in [-1, 1]
+    pub report: ConvergenceReport,
+}
+
+// Fairness / goodness algorithm of Kumar et al. (ICDM 2016), made for the
+// Bitcoin OTC / Alpha networks. Ratings are scaled to [-1, 1] by the largest
+// absolute weight, then until convergence:
+//   goodness(v) = mean over raters u of fairness(u) * w(u, v)
+//   fairness(u) = 1 - mean over rated v of |w(u, v) - goodness(v)| / 2
+// so ratings from unfair raters (e.g. scammers) count less. Nodes nobody
+// rated have goodness 0 and nodes that rated nobody keep fairness 1.
+pub fn fairness_goodness<G: Network>(graph: &G, options: &FairnessGoodnessOptions) -> FairnessGoodness {
+    let max_weight = graph.edges().map(|edge| edge.weight.abs()).fold(0.0, f64::max);
+    let scale = if max_weight > 0.0 { max_weight } else { 1.0 };
+
+    let mut fairness: HashMap<usize, f64> = graph.nodes().map(|node| (node, 1.0)).collect();
+    let mut goodness: HashMap<usize, f64> = graph.nodes().map(|node| (node, 1.0)).collect();
+    let mut report = ConvergenceReport { iterations: 0, converged: false, final_change: 0.0 };
+
+    while report.iterations < options.max_iterations {
+        let mut change: f64 = 0.0;
+
+        // Goodness from the fairness of the raters
+        for (&node, score) in goodness.iter_mut() {
+            let in_degree = graph.in_degree(node);
+            let new_score = if in_degree == 0 {
+                0.0
+            } else {
+                graph.in_edges(node).map(|edge| fairness[&edge.from] * edge.weight / scale).sum::<f64>() / in_degree as f64
+            };
+            change = change.max((new_score - *score).abs());
+            *score = new_score;
+        }
+
+        // Fairness from how far the ratings are from the goodness of the rated nodes
+        for (&node, score) in fairness.iter_mut() {
+            let out_degree = graph.out_degree(node);
+            let new_score = if out_degree == 0 {
+                1.0
+            } else {
+                let error: f64 = graph.out_edges(node).map(|edge| (edge.weight / scale - goodness[&edge.to]).abs()).sum();
+                1.0 - error / (2.0 * out_degree as f64)
+            };
+            change = change.max((new_score - *score).abs());
+            *score = new_score;
+        }
+
+        report.iterations += 1;
+        report.final_change = change;
+        if change < options.tolerance {
+            report.converged = true;
+            break;
+        }
+    }
+
+    FairnessGoodness { fairness, goodness, report }
+}
+
+// ----------------------- TESTS -----------------------
+
+#[test]
+fn test_pagerank_cycle_is_uniform() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 1, weight: 1.0, timestamp: None },
+    ];
+    let scores = pagerank(&Graph::new(&edges), &PageRankOptions::default());
+
+    for node in [1, 2, 3] {
+        assert!((scores[&node] - 1.0 / 3.0).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_pagerank_ignores_negative_ratings() {
+    // Everybody rates 1 positively and 2 negatively
+    let edges = vec![
+        Edge { from: 3, to: 1, weight: 10.0, timestamp: None },
+        Edge { from: 4, to: 1, weight: 10.0, timestamp: None },
+        Edge { from: 3, to: 2, weight: -10.0, timestamp: None },
+        Edge { from: 4, to: 2, weight: -10.0, timestamp: None },
+    ];
+    let scores = pagerank(&Graph::new(&edges), &PageRankOptions::default());
+
+    let total: f64 = scores.values().sum();
+    assert!((total - 1.0).abs() < 1e-9); // Dangling nodes don't leak score
+    assert!(scores[&1] > scores[&2]);
+    assert!((scores[&2] - scores[&3]).abs() < 1e-9); // 2 only gets random jumps, like 3
+}
+
+#[test]
+fn test_pagerank_weighted() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 9.0, timestamp: None },
+        Edge { from: 1, to: 3, weight: 1.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    let unweighted = pagerank(&graph, &PageRankOptions::default());
+    assert!((unweighted[&2] - unweighted[&3]).abs() < 1e-9);
+
+    let weighted = pagerank(&graph, &PageRankOptions { weighted: true, ..Default::default() });
+    assert!(weighted[&2] > weighted[&3]);
+}
+
+#[test]
+fn test_personalized_pagerank() {
+    // Two separate groups, 1 -> 2 and 3 -> 4
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 5.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: 5.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+
+    let scores = personalized_pagerank(&graph, &[1], &PageRankOptions::default());
+    assert!(scores[&2] > 0.0);
+    assert_eq!(scores[&3], 0.0); // Unreachable from the seed
+    assert_eq!(scores[&4], 0.0);
+
+    // Unknown seeds fall back to the ordinary PageRank
+    assert_eq!(personalized_pagerank(&graph, &[42], &PageRankOptions::default()), pagerank(&graph, &PageRankOptions::default()));
+}
+
+#[test]
+fn test_fairness_goodness() {
+    // 1, 2, 3, 5 and 6 agree that 10 is good and 11 is bad, 4 rates the opposite
+    let mut edges = Vec::new();
+    for rater in [1, 2, 3, 5, 6] {
+        edges.push(Edge { from: rater, to: 10, weight: 10.0, timestamp: None });
+        edges.push(Edge { from: rater, to: 11, weight: -10.0, timestamp: None });
+    }
+    edges.push(Edge { from: 4, to: 10, weight: -10.0, timestamp: None });
+    edges.push(Edge { from: 4, to: 11, weight: 10.0, timestamp: None });
+
+    let result = fairness_goodness(&Graph::new(&edges), &FairnessGoodnessOptions::default());
+
+    assert!(result.report.converged);
+    assert!(result.report.final_change < 1e-6);
+    assert!(result.goodness[&10] > 0.5);
+    assert!(result.goodness[&11] < -0.5);
+    assert!(result.fairness[&1] > result.fairness[&4]); // 4 disagrees with everyone
+    assert_eq!(result.goodness[&1], 0.0); // Nobody rated 1
+    assert_eq!(result.fairness[&10], 1.0); // 10 rated nobody
+}
+
+#[test]
+fn test_fairness_goodness_iteration_limit() {
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 4.0, timestamp: None },
+        Edge { from: 2, to: 1, weight: -2.0, timestamp: None },
+    ];
+    let options = FairnessGoodnessOptions { tolerance: 0.0, max_iterations: 3 };
+    let result = fairness_goodness(&Graph::new(&edges), &options);
+
+    assert_eq!(result.report.iterations, 3);
+    assert!(!result.report.converged);
+    for score in result.goodness.values() {
+        assert!((-1.0..=1.0).contains(score));
+    }
+}
+
+#[test]
+fn test_eigentrust() {
+    // 1 and 2 rate each other highly, both rate 3 a little, 4 is only rated negatively
+    let edges = vec![
+        Edge { from: 1, to: 2, weight: 9.0, timestamp: None },
+        Edge { from: 2, to: 1, weight: 9.0, timestamp: None },
+        Edge { from: 1, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 2, to: 3, weight: 1.0, timestamp: None },
+        Edge { from: 3, to: 4, weight: -5.0, timestamp: None },
+    ];
+    let graph = Graph::new(&edges);
+    let scores = eigentrust(&graph, &[], &EigenTrustOptions::default());
+
+    let total: f64 = scores.values().sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    assert!(scores[&1] > scores[&3]);
+    assert!(scores[&3] > scores[&4]);
+
+    // 3 trusts nobody, so with 3 as the only pre-trusted peer all trust stays with it
+    let pre_trusted = eigentrust(&graph, &[3], &EigenTrustOptions::default());
+    assert!((pre_trusted[&3] - 1.0).abs() < 1e-9);
+    assert_eq!(pre_trusted[&4], 0.0);
+}
@@ -0,0 +1,8 @@
+// Trust analysis of signed, weighted rating networks such as Bitcoin Alpha.
+//
+// graph       - the Graph / Edge structs and the graph algorithms
+// analyze     - GraphInfo, the per node statistics and the analysis on top of them
+// data_loader - reading edge lists (csv, tsv, SNAP, gzip) into Edges
+pub mod analyze;
+pub mod data_loader;
+pub mod graph;
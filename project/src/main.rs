@@ -1,55 +1,334 @@
-mod graph;
-use graph::Graph;
-mod data_loader;
-use data_loader::read_csv;
-mod analyze;
-use analyze::GraphInfo;
+use project::analyze::{spearman_correlation, CentralityMeasure, GraphInfo};
+use project::data_loader::{read_csv, read_csv_lenient};
+use project::graph::balance::triad_census;
+use project::graph::community::{counts_for_frustration, frustration, louvain, signed_louvain, Frustration, LouvainOptions, Partition};
+use project::graph::reputation::{eigentrust, personalized_pagerank, EigenTrustOptions, PageRankOptions};
+use project::graph::csr::CsrGraph;
+use project::graph::{ClusteringVariant, Edge, Network};
+use std::collections::HashMap;
+use std::env;
+use std::process;
+mod cli;
+use cli::{CliArgs, CliError, Command};
 
 fn main() {
-    let csv_path = "soc-sign-bitcoinalpha.csv";
-    
-    println!("\n------------- General Info -------------");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut cli_args: CliArgs = match cli::parse_args(&args) {
+        Ok(cli_args) => cli_args,
+        Err(CliError::Help) => {
+            println!("{}", cli::usage());
+            return;
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(2);
+        }
+    };
+
     // The list of edges corresponds to the data
-    let edge_lst: Vec<graph::Edge> = read_csv(csv_path);
+    let edge_lst: Vec<Edge> = load_edges(&cli_args);
+
+    // Make a compact graph using the list of edges, with dense node ids.
+    let graph: CsrGraph = CsrGraph::from_edges(&edge_lst);
 
-    // Make a more convinient graph using the list of edges.
-    let graph: Graph = Graph::new(&edge_lst);
+    // Seeds that aren't in the graph would be silently ignored by PageRank and EigenTrust
+    let unknown_seeds: Vec<usize> = cli_args.seeds.iter().copied().filter(|seed| !graph.contains(*seed)).collect();
+    if !unknown_seeds.is_empty() {
+        if unknown_seeds.len() == cli_args.seeds.len() {
+            eprintln!("None of the seeds {:?} are in the graph", unknown_seeds);
+            process::exit(2);
+        }
+        eprintln!("Ignoring seeds that are not in the graph: {:?}", unknown_seeds);
+        cli_args.seeds.retain(|seed| !unknown_seeds.contains(seed));
+    }
+
+    // Paths only need the graph, skip computing all the node statistics.
+    if cli_args.command == Command::Path {
+        print_path(&graph, &cli_args);
+        return;
+    }
 
-    let graph_info: GraphInfo = GraphInfo::get_info(&graph.clone());
+    let graph_info: GraphInfo<CsrGraph> = GraphInfo::get_info_with(&graph, cli_args.clustering);
+
+    let command = cli_args.command;
+    if command == Command::All || command == Command::Info {
+        print_info(&graph_info);
+    }
+    if command == Command::All || command == Command::Components {
+        print_components(&graph_info);
+    }
+    if command == Command::All || command == Command::Communities {
+        print_communities(&graph_info, &cli_args);
+    }
+    if command == Command::All || command == Command::Centrality {
+        print_centrality(&graph_info, &cli_args);
+    }
+    if command == Command::All || command == Command::Representatives {
+        print_representatives(&graph_info, &cli_args);
+    }
+    if command == Command::All || command == Command::Trust {
+        print_trust(&graph_info, &cli_args);
+    }
+    if command == Command::All || command == Command::Balance {
+        print_balance(&graph_info, &cli_args);
+    }
+}
+
+// Load the edge list, exiting with a message if it can't be read.
+// With --lenient malformed rows are reported and skipped.
+fn load_edges(cli_args: &CliArgs) -> Vec<Edge> {
+    let csv_path = cli_args.input.as_str();
+
+    if cli_args.lenient {
+        match read_csv_lenient(csv_path) {
+            Ok(report) => {
+                for skipped in report.skipped.iter() {
+                    eprintln!("Skipped {}", skipped);
+                }
+                println!("\nTotal number of edges: {} ({} rows skipped)\n", report.edges.len(), report.skipped.len());
+                report.edges
+            }
+            Err(err) => {
+                eprintln!("Could not load {}: {}", csv_path, err);
+                process::exit(1);
+            }
+        }
+    } else {
+        match read_csv(csv_path) {
+            Ok(edges) => {
+                println!("\nTotal number of edges: {}\n", edges.len());
+                edges
+            }
+            Err(err) => {
+                eprintln!("Could not load {}: {}\n(use --lenient to skip malformed rows)", csv_path, err);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+fn print_info(graph_info: &GraphInfo<CsrGraph>) {
+    println!("\n------------- General Info -------------\n");
 
     // number of total nodes
-    let num_nodes: usize = graph_info.graph.content.len();
+    let num_nodes: usize = graph_info.graph.node_count();
     println!("Number of nodes in this data: {}\n", num_nodes);
 
+    // Time span covered by the ratings
+    if let Some((first_rating, last_rating)) = graph_info.graph.time_range() {
+        println!("Ratings made between timestamps {} and {}\n", first_rating, last_rating);
+    }
+
     // Number of sub graphs
-    let num_sub_graphs: usize = graph_info.sub_graphs.len();
+    let num_sub_graphs: usize = graph_info.sub_graphs().len();
     println!("Number of subgraphs in this data: {}\n", num_sub_graphs);
 
-    // Average clustering coefficient
-    let clustering_coefficient_sum: f64 = graph_info.clustering_coefficients.values().sum();
+    // Number of strongly connected sub graphs
+    let num_strong_sub_graphs: usize = graph_info.strong_sub_graphs().len();
+    println!("Number of strongly connected subgraphs in this data: {}\n", num_strong_sub_graphs);
+
+    // Densest part of the graph
+    let (max_core, innermost_size) = graph_info.innermost_core();
+    println!("Innermost core: {}-core with {} nodes\n", max_core, innermost_size);
+
+    // Average clustering coefficient, the --clustering variant used by the other sections
+    let clustering_coefficient_sum: f64 = graph_info.clustering_coefficients().values().sum();
     let avg_clustering_coefficient: f64 = clustering_coefficient_sum / num_nodes as f64;
-    println!("Average clustering coefficient: {:.5}\n", avg_clustering_coefficient);
+    let clustering_name = match graph_info.clustering_variant {
+        ClusteringVariant::Unweighted => "unweighted, ratings between neighbours over possible ratings",
+        ClusteringVariant::Onnela => "Onnela, weighted",
+        ClusteringVariant::Barrat => "Barrat, weighted",
+        ClusteringVariant::Signed => "signed, balanced minus unbalanced triangles",
+    };
+    println!("Average clustering coefficient ({}): {:.5}\n", clustering_name, avg_clustering_coefficient);
+
+    // Triangles of the undirected graph
+    let triangles = graph_info.triangles();
+    println!("Number of triangles: {}\n", triangles.total_triangles);
+    println!("Transitivity: {:.5}\n", triangles.transitivity);
+    println!("Average local clustering (triangles over neighbour pairs, undirected / directed Fagiolo): {:.5} / {:.5}\n",
+             triangles.average_clustering, triangles.average_directed_clustering);
 
     // Average trust score
     let trust_score_sum: f64 = graph_info.trust_scores.values().sum();
     let avg_trust_score: f64 = trust_score_sum / num_nodes as f64;
-    println!("Average trust score: {:.5}\n", avg_trust_score);
+    println!("Average trust score: {:.5}", avg_trust_score);
+}
 
-    let mut graph_index = 1;
+fn print_components(graph_info: &GraphInfo<CsrGraph>) {
+    println!("\n------------- Sub graphs -------------\n");
 
-    for sub_graph in graph_info.sub_graphs.iter() {
-        println!("Number of nodes in sub graph {}: {}", graph_index, sub_graph.content.len());
-        graph_index += 1;
+    for (graph_index, sub_graph) in (1..).zip(graph_info.sub_graphs().iter()) {
+        println!("Number of nodes in sub graph {}: {}", graph_index, sub_graph.node_count());
     }
 
+    println!();
+    for (graph_index, diameter) in (1..).zip(graph_info.sub_graph_diameters().iter()) {
+        let estimated = if diameter.exact { "" } else { " (double sweep estimate)" };
+        println!("Diameter of sub graph {}: {}{}", graph_index, diameter.value, estimated);
+    }
+
+    // Most strongly connected components are single nodes, only list the others.
+    let strong_sizes: Vec<usize> = graph_info.strong_component_sizes();
+    let singletons: usize = strong_sizes.iter().filter(|&&size| size == 1).count();
+    println!("\nNumber of strongly connected sub graphs: {} ({} with a single node)", strong_sizes.len(), singletons);
+    for (graph_index, size) in (1..).zip(strong_sizes.iter().filter(|&&size| size > 1)) {
+        println!("Number of nodes in strongly connected sub graph {}: {}", graph_index, size);
+    }
+}
+
+fn print_communities(graph_info: &GraphInfo<CsrGraph>, cli_args: &CliArgs) {
+    println!("\n------------- Communities (Louvain, positive ratings) -------------\n");
+    let partition = louvain(&graph_info.graph, &LouvainOptions::default());
+    print_partition(graph_info, &partition, "Modularity", cli_args.k);
+
+    println!("\n------------- Signed communities (Louvain, signed modularity) -------------\n");
+    let signed_partition = signed_louvain(&graph_info.graph, &LouvainOptions::default());
+    print_partition(graph_info, &signed_partition, "Signed modularity", cli_args.k);
+
+    // Positive ratings between groups and negative ratings inside them, for each way of grouping
+    let num_edges = graph_info.graph.edges().filter(counts_for_frustration).count();
+    let components = graph_info.graph.nodes().map(|node| (node, graph_info.component_of(node).unwrap()))
+        .collect();
+    println!("\nFrustration (positive ratings between + negative ratings within groups, out of {} ratings):", num_edges);
+    let groupings = [
+        ("weak components", frustration(&graph_info.graph, &components)),
+        ("Louvain", partition.frustration(&graph_info.graph)),
+        ("signed Louvain", signed_partition.frustration(&graph_info.graph)),
+    ];
+    for (name, Frustration { positive_between, negative_within }) in groupings {
+        println!("  {}: {} + {} = {}", name, positive_between, negative_within, positive_between + negative_within);
+    }
+}
+
+// Number of communities, modularity and the summary of the k largest communities.
+fn print_partition(graph_info: &GraphInfo<CsrGraph>, partition: &Partition, modularity_name: &str, k: usize) {
+    let summaries = graph_info.summarize_communities(partition);
+    let singletons = summaries.iter().filter(|summary| summary.size == 1).count();
+    println!("Number of communities: {} ({} with a single node)", partition.len(), singletons);
+    println!("{}: {:.5}\n", modularity_name, partition.modularity);
+
+    for (community_index, summary) in (1..).zip(summaries.iter().take(k)) {
+        println!("Community {}: {} nodes, average trust score {:.3}, average clustering coefficient {:.5}",
+                 community_index, summary.size, summary.average_trust, summary.average_clustering);
+    }
+}
+
+fn print_centrality(graph_info: &GraphInfo<CsrGraph>, cli_args: &CliArgs) {
     println!("\n------------- Clustering and Centrality of nodes with high / low trust score -------------");
 
-    let clustering_centrality_result: String = graph_info.clone().analyze_clustering_centrality(4.0, -2.0);
+    let measure = cli_args.centrality.unwrap_or(CentralityMeasure::OutDegree);
+    let clustering_centrality_result: String =
+        graph_info.analyze_clustering_centrality_with(cli_args.high_score, cli_args.low_score, measure);
     println!("{}", clustering_centrality_result);
+}
 
-    let k: usize = 15;
+fn print_representatives(graph_info: &GraphInfo<CsrGraph>, cli_args: &CliArgs) {
     println!("\n------------- K representatives -------------");
-    let k_representatives_result: String = graph_info.clone().find_k_representatives(k);
+    let measure = cli_args.centrality.unwrap_or(CentralityMeasure::InDegree);
+    let k_representatives_result: String = graph_info.find_k_representatives_with(cli_args.k, measure);
     println!("{}", k_representatives_result);
 }
 
+fn print_trust(graph_info: &GraphInfo<CsrGraph>, cli_args: &CliArgs) {
+    println!("\n------------- Trust scores -------------\n");
+
+    let num_nodes: usize = graph_info.trust_scores.len();
+    let trust_score_sum: f64 = graph_info.trust_scores.values().sum();
+    println!("Average trust score: {:.5}\n", trust_score_sum / num_nodes as f64);
+
+    let high_count = graph_info.trust_scores.values().filter(|&&score| score >= cli_args.high_score).count();
+    let low_count = graph_info.trust_scores.values().filter(|&&score| score <= cli_args.low_score).count();
+    println!("Nodes with trust score >= {}: {}", cli_args.high_score, high_count);
+    println!("Nodes with trust score <= {}: {}\n", cli_args.low_score, low_count);
+
+    let ranked: Vec<(usize, f64)> = rank_nodes(&graph_info.trust_scores);
+    let k = cli_args.k.min(ranked.len());
+    println!("Most trusted {} nodes: {:?}\n", k, &ranked[..k]);
+    println!("Least trusted {} nodes: {:?}\n", k, ranked[ranked.len() - k..].iter().rev().collect::<Vec<_>>());
+
+    // Fairness / goodness
+    let fairness_goodness = graph_info.fairness_goodness();
+    let report = &fairness_goodness.report;
+    let convergence = if report.converged { "converged" } else { "did not converge" };
+    println!("Fairness / goodness {} after {} iterations (last change {:.2e})\n", convergence, report.iterations, report.final_change);
+    let ranked_goodness: Vec<(usize, f64)> = rank_nodes(&fairness_goodness.goodness);
+    println!("Highest goodness {} nodes: {:?}\n", k, &ranked_goodness[..k]);
+    println!("Lowest goodness {} nodes: {:?}\n", k, ranked_goodness[ranked_goodness.len() - k..].iter().rev().collect::<Vec<_>>());
+    let ranked_fairness: Vec<(usize, f64)> = rank_nodes(&fairness_goodness.fairness);
+    println!("Least fair {} raters: {:?}\n", k, ranked_fairness[ranked_fairness.len() - k..].iter().rev().collect::<Vec<_>>());
+
+    let ranked_pagerank: Vec<(usize, f64)> = rank_nodes(graph_info.pagerank());
+    println!("Highest PageRank {} nodes: {:?}", k, &ranked_pagerank[..k]);
+
+    if !cli_args.seeds.is_empty() {
+        let personalized = personalized_pagerank(&graph_info.graph, &cli_args.seeds, &PageRankOptions::default());
+        let ranked_personalized: Vec<(usize, f64)> = rank_nodes(&personalized);
+        println!("\nHighest PageRank {} nodes from the viewpoint of {:?}: {:?}", k, cli_args.seeds, &ranked_personalized[..k]);
+    }
+
+    // EigenTrust, with the seeds as pre-trusted peers if there are any
+    let eigentrust_scores = if cli_args.seeds.is_empty() {
+        graph_info.eigentrust().clone()
+    } else {
+        eigentrust(&graph_info.graph, &cli_args.seeds, &EigenTrustOptions::default())
+    };
+    let ranked_eigentrust: Vec<(usize, f64)> = rank_nodes(&eigentrust_scores);
+    println!("\nHighest EigenTrust {} nodes: {:?}\n", k, &ranked_eigentrust[..k]);
+
+    // How well each algorithm agrees with the plain trust score
+    println!("Rank correlation with the trust score (Spearman):");
+    let alternatives = [
+        ("goodness", &fairness_goodness.goodness),
+        ("PageRank", graph_info.pagerank()),
+        ("EigenTrust", &eigentrust_scores),
+    ];
+    for (name, scores) in alternatives {
+        match spearman_correlation(&graph_info.trust_scores, scores) {
+            Some(correlation) => println!("  {}: {:.4}", name, correlation),
+            None => println!("  {}: undefined", name),
+        }
+    }
+}
+
+fn print_balance(graph_info: &GraphInfo<CsrGraph>, cli_args: &CliArgs) {
+    println!("\n------------- Structural balance -------------\n");
+
+    println!("Signed triangles (ratings in either direction, summed):");
+    println!("{}\n", graph_info.balance());
+
+    // Nodes in the most triangles, and how balanced their triangles are
+    let mut busiest: Vec<(usize, usize)> = graph_info.balance().node_triangles.iter().map(|(&node, &count)| (node, count)).collect();
+    busiest.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    println!("Balance of the {} nodes in the most triangles:", cli_args.k.min(busiest.len()));
+    for &(node, triangles) in busiest.iter().take(cli_args.k) {
+        println!("  {}: {:.2}% of {} triangles", node, graph_info.balance().node_balance(node).unwrap() * 100.0, triangles);
+    }
+
+    println!("\nDirected triad census:");
+    println!("{}", triad_census(&graph_info.graph));
+
+    println!("\nStatus theory, ratings A -> B by how a common neighbour X is linked (u+>v: u rates v positively):\n");
+    println!("{}", graph_info.analyze_status());
+}
+
+// Nodes sorted by score, highest first. Ties are broken by node id so the output is stable.
+fn rank_nodes(scores: &HashMap<usize, f64>) -> Vec<(usize, f64)> {
+    let mut ranked: Vec<(usize, f64)> = scores.iter().map(|(&node, &score)| (node, score)).collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    ranked
+}
+
+fn print_path(graph: &CsrGraph, cli_args: &CliArgs) {
+    println!("\n------------- Trust path -------------\n");
+
+    // parse_args makes sure both are set for the path command
+    let (from, to) = (cli_args.from.unwrap(), cli_args.to.unwrap());
+    match graph.shortest_path(from, to) {
+        Some((distance, path)) => {
+            println!("Trust distance from {} to {}: {}\n", from, to, distance);
+            println!("Path ({} ratings): {:?}", path.len() - 1, path);
+        }
+        None => println!("No path from {} to {} through positive ratings", from, to),
+    }
+}